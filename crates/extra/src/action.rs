@@ -1,4 +1,8 @@
-use ltrait::{Action, action::ActionWrapper};
+use ltrait::{
+    Action,
+    action::ActionWrapper,
+    color_eyre::eyre::{Result, WrapErr},
+};
 
 impl<T> ActionExt for T
 where
@@ -20,6 +24,187 @@ where
     {
         ActionIf::new(self, f, transformer)
     }
+
+    /// Run `other` after `self` succeeded
+    ///
+    /// Combine actions with other contexts by wrapping them with [`ActionWrapper`]:
+    ///
+    /// ```
+    /// # use ltrait::{Action, action::{ActionWrapper, ClosureAction}};
+    /// # use ltrait_extra::action::ActionExt as _;
+    /// struct Item {
+    ///     exec: String,
+    ///     ident: String,
+    /// }
+    ///
+    /// let launch = ClosureAction::new(|item: &Item| Ok(println!("launch {}", item.exec)));
+    /// let bump = ClosureAction::new(|ident: &String| Ok(println!("visited {ident}")));
+    /// let terminal = ClosureAction::new(|_: &Item| Ok(println!("open a terminal")));
+    ///
+    /// let action = launch
+    ///     .then(ActionWrapper::new(bump, |item: &Item| item.ident.clone()))
+    ///     .or_else(terminal);
+    /// ```
+    fn then<T>(self, other: T) -> impl Action<Context = <Self as Action>::Context>
+    where
+        Self: Sized,
+        T: Action<Context = <Self as Action>::Context>,
+    {
+        ActionThen::new(self, other)
+    }
+
+    /// Run `fallback` if `self` failed. If `fallback` fails too, its error is returned
+    fn or_else<T>(self, fallback: T) -> impl Action<Context = <Self as Action>::Context>
+    where
+        Self: Sized,
+        T: Action<Context = <Self as Action>::Context>,
+    {
+        ActionOrElse::new(self, fallback)
+    }
+
+    /// Run `self` and discard its error, so that it always succeeds
+    fn ignore_errors(self) -> impl Action<Context = <Self as Action>::Context>
+    where
+        Self: Sized,
+    {
+        ActionIgnoreErrors::new(self)
+    }
+
+    /// Call `f` (e.g. to log) before running `self`
+    fn inspect<F>(self, f: F) -> impl Action<Context = <Self as Action>::Context>
+    where
+        Self: Sized,
+        F: Fn(&<Self as Action>::Context) + Send,
+    {
+        ActionInspect::new(self, f)
+    }
+}
+
+pub struct ActionThen<T1, T2, Ctx>
+where
+    T1: Action<Context = Ctx>,
+    T2: Action<Context = Ctx>,
+{
+    action1: T1,
+    action2: T2,
+}
+
+impl<T1, T2, Ctx> ActionThen<T1, T2, Ctx>
+where
+    T1: Action<Context = Ctx>,
+    T2: Action<Context = Ctx>,
+{
+    pub fn new(action1: T1, action2: T2) -> Self {
+        Self { action1, action2 }
+    }
+}
+
+impl<T1, T2, Ctx> Action for ActionThen<T1, T2, Ctx>
+where
+    T1: Action<Context = Ctx>,
+    T2: Action<Context = Ctx>,
+{
+    type Context = Ctx;
+
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
+        self.action1.act(ctx)?;
+        self.action2.act(ctx)
+    }
+}
+
+pub struct ActionOrElse<T1, T2, Ctx>
+where
+    T1: Action<Context = Ctx>,
+    T2: Action<Context = Ctx>,
+{
+    action: T1,
+    fallback: T2,
+}
+
+impl<T1, T2, Ctx> ActionOrElse<T1, T2, Ctx>
+where
+    T1: Action<Context = Ctx>,
+    T2: Action<Context = Ctx>,
+{
+    pub fn new(action: T1, fallback: T2) -> Self {
+        Self { action, fallback }
+    }
+}
+
+impl<T1, T2, Ctx> Action for ActionOrElse<T1, T2, Ctx>
+where
+    T1: Action<Context = Ctx>,
+    T2: Action<Context = Ctx>,
+{
+    type Context = Ctx;
+
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
+        self.action.act(ctx).or_else(|e| {
+            self.fallback
+                .act(ctx)
+                .wrap_err_with(|| format!("the fallback failed after: {e:#}"))
+        })
+    }
+}
+
+pub struct ActionIgnoreErrors<T, Ctx>
+where
+    T: Action<Context = Ctx>,
+{
+    action: T,
+}
+
+impl<T, Ctx> ActionIgnoreErrors<T, Ctx>
+where
+    T: Action<Context = Ctx>,
+{
+    pub fn new(action: T) -> Self {
+        Self { action }
+    }
+}
+
+impl<T, Ctx> Action for ActionIgnoreErrors<T, Ctx>
+where
+    T: Action<Context = Ctx>,
+{
+    type Context = Ctx;
+
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
+        let _ = self.action.act(ctx);
+        Ok(())
+    }
+}
+
+pub struct ActionInspect<T, Ctx, F>
+where
+    T: Action<Context = Ctx>,
+    F: Fn(&Ctx) + Send,
+{
+    action: T,
+    f: F,
+}
+
+impl<T, Ctx, F> ActionInspect<T, Ctx, F>
+where
+    T: Action<Context = Ctx>,
+    F: Fn(&Ctx) + Send,
+{
+    pub fn new(action: T, f: F) -> Self {
+        Self { action, f }
+    }
+}
+
+impl<T, Ctx, F> Action for ActionInspect<T, Ctx, F>
+where
+    T: Action<Context = Ctx>,
+    F: Fn(&Ctx) + Send,
+{
+    type Context = Ctx;
+
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
+        (self.f)(ctx);
+        self.action.act(ctx)
+    }
 }
 
 pub struct ActionIf<T, Ctx, F>
@@ -59,7 +244,7 @@ where
 {
     type Context = Ctx;
 
-    fn act(&self, ctx: &Self::Context) -> ltrait::color_eyre::eyre::Result<()> {
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
         if (self.f)(ctx) {
             self.inner.act(ctx)
         } else {
@@ -67,3 +252,47 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ltrait::{action::ClosureAction, color_eyre::eyre::bail};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_combinators() {
+        let log = Mutex::new(vec![]);
+        let push = |name: &'static str, ok: bool| {
+            let log = &log;
+            ClosureAction::new(move |_: &()| {
+                log.lock().unwrap().push(name);
+                if ok { Ok(()) } else { bail!("{name} failed") }
+            })
+        };
+
+        assert!(push("a", true).then(push("b", true)).act(&()).is_ok());
+        assert!(push("c", false).then(push("d", true)).act(&()).is_err());
+        assert!(push("e", false).or_else(push("f", true)).act(&()).is_ok());
+        assert!(push("g", true).or_else(push("h", true)).act(&()).is_ok());
+        assert!(push("i", false).ignore_errors().act(&()).is_ok());
+
+        let err = push("j", false)
+            .or_else(push("k", false))
+            .act(&())
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "the fallback failed after: j failed: k failed"
+        );
+
+        push("m", true)
+            .inspect(|_| log.lock().unwrap().push("l"))
+            .act(&())
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["a", "b", "c", "e", "f", "g", "i", "j", "k", "l", "m"]
+        );
+    }
+}
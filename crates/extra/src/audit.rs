@@ -0,0 +1,209 @@
+//! An action that appends a JSON Lines record for each launch, e.g. to find out why an entry was
+//! launched or to analyze the ranking later.
+
+use ltrait::{
+    Action,
+    color_eyre::eyre::{Result, WrapErr},
+};
+use serde::{Deserialize, Serialize};
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// What is logged for a launch, given by the transformer of [`AuditLog`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Identifies the launched entry (e.g. the path of the desktop entry)
+    pub ident: String,
+    pub label: String,
+    /// The input when the entry was launched (e.g. from `ltrait_ui_tui::LastQuery`)
+    pub query: String,
+    pub mode: Option<String>,
+}
+
+/// A line of the log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub entry: AuditEntry,
+}
+
+/// Appends an [`AuditRecord`] to `path` on each launch.
+///
+/// When the log would exceed [`AuditLog::max_size`], it's renamed to `<path>.1` (and `<path>.1`
+/// to `<path>.2`, and so on, up to [`AuditLog::keep`] files) and a new log is started.
+///
+/// ```no_run
+/// # use ltrait_extra::audit::{AuditEntry, AuditLog};
+/// struct App {
+///     path: String,
+///     name: String,
+/// }
+///
+/// let log = AuditLog::new("/tmp/launches.jsonl", |app: &App| AuditEntry {
+///     ident: app.path.clone(),
+///     label: app.name.clone(),
+///     ..Default::default()
+/// });
+/// ```
+pub struct AuditLog<Ctx, F>
+where
+    F: Fn(&Ctx) -> AuditEntry + Send,
+{
+    path: PathBuf,
+    f: F,
+    max_size: u64,
+    keep: usize,
+    // 同時に書き込んでrotateが壊れないように
+    lock: Mutex<()>,
+
+    _ctx: PhantomData<fn(&Ctx)>,
+}
+
+impl<Ctx, F> AuditLog<Ctx, F>
+where
+    F: Fn(&Ctx) -> AuditEntry + Send,
+{
+    pub fn new(path: impl Into<PathBuf>, f: F) -> Self {
+        Self {
+            path: path.into(),
+            f,
+            max_size: 10 * 1024 * 1024,
+            keep: 3,
+            lock: Mutex::new(()),
+            _ctx: PhantomData,
+        }
+    }
+
+    /// The size in bytes at which the log is rotated.
+    /// The default value is 10 MiB.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    /// The number of rotated logs kept. `0` discards the log when it's rotated.
+    /// The default value is 3.
+    pub fn keep(mut self, count: usize) -> Self {
+        self.keep = count;
+        self
+    }
+
+    fn rotated(&self, i: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{i}"));
+        path.into()
+    }
+
+    fn rotate(&self) -> Result<()> {
+        if self.keep == 0 {
+            return Ok(std::fs::remove_file(&self.path)?);
+        }
+
+        for i in (1..self.keep).rev() {
+            let from = self.rotated(i);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated(i + 1))?;
+            }
+        }
+
+        Ok(std::fs::rename(&self.path, self.rotated(1))?)
+    }
+}
+
+impl<Ctx, F> Action for AuditLog<Ctx, F>
+where
+    F: Fn(&Ctx) -> AuditEntry + Send,
+{
+    type Context = Ctx;
+
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
+        let record = AuditRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            entry: (self.f)(ctx),
+        };
+
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+
+        let _lock = self.lock.lock().unwrap();
+
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_size {
+            self.rotate()
+                .wrap_err_with(|| format!("failed to rotate {}", self.path.display()))?;
+        }
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .wrap_err_with(|| format!("failed to write to {}", self.path.display()))
+    }
+}
+
+/// Read the records of a log, e.g. for analysis.
+pub fn read(path: &Path) -> Result<Vec<AuditRecord>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate() {
+        let dir = std::env::temp_dir().join(format!("ltrait-extra-audit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("audit.jsonl");
+
+        let log = AuditLog::new(&path, |ident: &&str| AuditEntry {
+            ident: ident.to_string(),
+            label: "label".into(),
+            query: "q".into(),
+            mode: None,
+        })
+        .max_size(150)
+        .keep(2);
+
+        for ident in ["a", "b", "c", "d"] {
+            log.act(&ident).unwrap();
+        }
+
+        let idents = |path: &Path| -> Vec<String> {
+            read(path)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.entry.ident)
+                .collect()
+        };
+
+        // a record is about 80 bytes, so each file holds one
+        assert_eq!(idents(&path), ["d"]);
+        assert_eq!(idents(&log.rotated(1)), ["c"]);
+        assert_eq!(idents(&log.rotated(2)), ["b"]);
+        assert!(!log.rotated(3).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+//! An action that copies text (e.g. the result of a calculator) to the clipboard.
+
+use ltrait::{
+    Action,
+    color_eyre::eyre::{Result, WrapErr, ensure},
+};
+
+use std::{
+    io::Write,
+    marker::PhantomData,
+    path::Path,
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Clipboard,
+    /// The primary selection (pasted with the middle click)
+    Primary,
+}
+
+#[derive(Debug, Clone)]
+pub enum Backend {
+    /// `wl-copy` on Wayland, `xclip` or `xsel` on X11 (whichever is installed), otherwise OSC 52
+    Auto,
+    WlCopy,
+    Xclip,
+    Xsel,
+    /// OSC 52 escape sequence written to `/dev/tty`. The terminal must support it
+    Osc52,
+    /// Records the copied text instead of copying it
+    Test(TestClipboard),
+}
+
+/// Records what was copied with [`Backend::Test`]. Clones share the record.
+#[derive(Debug, Clone, Default)]
+pub struct TestClipboard(Arc<Mutex<Vec<(Selection, String)>>>);
+
+impl TestClipboard {
+    /// Everything copied so far, oldest first.
+    pub fn copied(&self) -> Vec<(Selection, String)> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Backend {
+    /// Resolve [`Backend::Auto`] from the environment.
+    fn resolve(&self) -> Self {
+        if !matches!(self, Self::Auto) {
+            return self.clone();
+        }
+
+        let has_var = |key| std::env::var_os(key).is_some_and(|v| !v.is_empty());
+
+        if has_var("WAYLAND_DISPLAY") && in_path("wl-copy") {
+            Self::WlCopy
+        } else if has_var("DISPLAY") && in_path("xclip") {
+            Self::Xclip
+        } else if has_var("DISPLAY") && in_path("xsel") {
+            Self::Xsel
+        } else {
+            Self::Osc52
+        }
+    }
+}
+
+fn in_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file())
+    })
+}
+
+/// Copies the text given by `text` from the context.
+///
+/// ```
+/// # use ltrait::Action;
+/// use ltrait_extra::clipboard::{Backend, ClipboardAction, Selection, TestClipboard};
+///
+/// let clipboard = TestClipboard::default();
+/// let copy = ClipboardAction::new(|result: &String| result.clone())
+///     .backend(Backend::Test(clipboard.clone()));
+///
+/// copy.act(&"42".into())?;
+/// assert_eq!(clipboard.copied(), vec![(Selection::Clipboard, "42".into())]);
+/// # Ok::<(), ltrait::color_eyre::Report>(())
+/// ```
+pub struct ClipboardAction<Ctx, F>
+where
+    F: Fn(&Ctx) -> String + Send,
+{
+    text: F,
+    backend: Backend,
+    selection: Selection,
+
+    _ctx: PhantomData<fn(&Ctx)>,
+}
+
+impl<Ctx, F> ClipboardAction<Ctx, F>
+where
+    F: Fn(&Ctx) -> String + Send,
+{
+    pub fn new(text: F) -> Self {
+        Self {
+            text,
+            backend: Backend::Auto,
+            selection: Selection::Clipboard,
+            _ctx: PhantomData,
+        }
+    }
+
+    /// The default value is [`Backend::Auto`].
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// The default value is [`Selection::Clipboard`].
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+}
+
+impl<Ctx, F> Action for ClipboardAction<Ctx, F>
+where
+    F: Fn(&Ctx) -> String + Send,
+{
+    type Context = Ctx;
+
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
+        let text = (self.text)(ctx);
+        let primary = self.selection == Selection::Primary;
+
+        match self.backend.resolve() {
+            Backend::WlCopy => pipe("wl-copy", if primary { &["--primary"] } else { &[] }, &text),
+            Backend::Xclip => pipe(
+                "xclip",
+                &[
+                    "-selection",
+                    if primary { "primary" } else { "clipboard" },
+                    "-in",
+                ],
+                &text,
+            ),
+            Backend::Xsel => pipe(
+                "xsel",
+                &[if primary { "--primary" } else { "--clipboard" }, "--input"],
+                &text,
+            ),
+            Backend::Osc52 => {
+                let mut tty = std::fs::OpenOptions::new()
+                    .write(true)
+                    .open("/dev/tty")
+                    .wrap_err("failed to open /dev/tty")?;
+                tty.write_all(osc52(&text, self.selection).as_bytes())?;
+                tty.flush()?;
+                Ok(())
+            }
+            Backend::Test(clipboard) => {
+                clipboard.0.lock().unwrap().push((self.selection, text));
+                Ok(())
+            }
+            Backend::Auto => unreachable!("resolved above"),
+        }
+    }
+}
+
+/// Run `program` with `text` as stdin.
+fn pipe(program: &str, args: &[&str], text: &str) -> Result<()> {
+    // xclipなどはselectionを保持するためにforkして残るので、stdoutを繋いだままにしない
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .wrap_err_with(|| format!("failed to spawn {program}"))?;
+
+    child.stdin.take().unwrap().write_all(text.as_bytes())?;
+
+    let status = child.wait()?;
+    ensure!(status.success(), "{program} exited with {status}");
+
+    Ok(())
+}
+
+fn osc52(text: &str, selection: Selection) -> String {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let target = match selection {
+        Selection::Clipboard => 'c',
+        Selection::Primary => 'p',
+    };
+
+    format!(
+        "\x1b]52;{target};{}\x07",
+        general_purpose::STANDARD.encode(text)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("42", Selection::Clipboard), "\x1b]52;c;NDI=\x07");
+        assert_eq!(osc52("", Selection::Primary), "\x1b]52;p;\x07");
+    }
+
+    #[test]
+    fn test_backend() {
+        let clipboard = TestClipboard::default();
+        let copy = |selection| {
+            ClipboardAction::new(|s: &&str| s.to_uppercase())
+                .backend(Backend::Test(clipboard.clone()))
+                .selection(selection)
+        };
+
+        copy(Selection::Clipboard).act(&"a").unwrap();
+        copy(Selection::Primary).act(&"b").unwrap();
+
+        assert_eq!(
+            clipboard.copied(),
+            vec![
+                (Selection::Clipboard, "A".into()),
+                (Selection::Primary, "B".into())
+            ]
+        );
+    }
+}
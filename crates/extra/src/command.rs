@@ -0,0 +1,282 @@
+//! An action that spawns a command built from the context.
+
+use ltrait::{
+    Action,
+    color_eyre::eyre::{Result, WrapErr, bail, ensure, eyre},
+};
+
+use std::{
+    fs::OpenOptions,
+    marker::PhantomData,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// Where the stdout and the stderr of the command go. Stdin is always `/dev/null`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    Null,
+    Inherit,
+    /// Append both to the file, creating it if it doesn't exist
+    Log(PathBuf),
+}
+
+/// Spawns a command whose arguments are filled from the context.
+///
+/// Each argument of `argv` is a template where `{name}` is replaced with the value of the
+/// placeholder `name` (given by `fill`), and `{{` and `}}` are literal braces.
+/// The command is run without a shell, so a value is always a part of a single argument
+/// whatever it contains. Use `{name:q}` to quote the value for a shell, e.g. in `sh -c`.
+///
+/// ```
+/// # use ltrait::Action;
+/// # use ltrait_extra::command::CommandAction;
+/// struct App {
+///     exec: String,
+///     url: String,
+/// }
+///
+/// let open = CommandAction::new(["sh", "-c", "{exec} {url:q}"], |app: &App, name| match name {
+///     "exec" => Some(app.exec.clone()),
+///     "url" => Some(app.url.clone()),
+///     _ => None,
+/// })
+/// .wait(true);
+///
+/// open.act(&App {
+///     exec: "true".into(),
+///     url: "https://example.com/?q=a b'c".into(),
+/// })?;
+/// # Ok::<(), ltrait::color_eyre::Report>(())
+/// ```
+pub struct CommandAction<Ctx, F>
+where
+    F: Fn(&Ctx, &str) -> Option<String> + Send,
+{
+    argv: Vec<String>,
+    fill: F,
+
+    current_dir: Option<PathBuf>,
+    envs: Vec<(String, String)>,
+    setsid: bool,
+    output: Output,
+    wait: bool,
+
+    _ctx: PhantomData<fn(&Ctx)>,
+}
+
+impl<Ctx, F> CommandAction<Ctx, F>
+where
+    F: Fn(&Ctx, &str) -> Option<String> + Send,
+{
+    pub fn new(argv: impl IntoIterator<Item = impl Into<String>>, fill: F) -> Self {
+        Self {
+            argv: argv.into_iter().map(Into::into).collect(),
+            fill,
+            current_dir: None,
+            envs: vec![],
+            setsid: true,
+            output: Output::Null,
+            wait: false,
+            _ctx: PhantomData,
+        }
+    }
+
+    /// The default value is None (the current directory of the launcher).
+    pub fn current_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.current_dir = dir;
+        self
+    }
+
+    /// Set an environment variable of the command, in addition to the launcher's ones.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Start the command in a new session so that it keeps running after the launcher (and its
+    /// terminal) exits. Ignored on non-unix platforms.
+    /// The default value is true.
+    pub fn setsid(mut self, flag: bool) -> Self {
+        self.setsid = flag;
+        self
+    }
+
+    /// The default value is [`Output::Null`].
+    pub fn output(mut self, output: Output) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Wait for the command to exit, failing if it exits unsuccessfully.
+    /// The default value is false.
+    pub fn wait(mut self, flag: bool) -> Self {
+        self.wait = flag;
+        self
+    }
+
+    fn command(&self, ctx: &Ctx) -> Result<Command> {
+        let argv = self
+            .argv
+            .iter()
+            .map(|template| expand(template, |name| (self.fill)(ctx, name)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let Some((program, args)) = argv.split_first() else {
+            bail!("the command is empty");
+        };
+
+        let mut command = Command::new(program);
+        command.args(args).envs(self.envs.iter().cloned());
+
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+
+        command.stdin(Stdio::null());
+        match &self.output {
+            Output::Null => {
+                command.stdout(Stdio::null()).stderr(Stdio::null());
+            }
+            Output::Inherit => {}
+            Output::Log(path) => {
+                let log = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .wrap_err_with(|| format!("failed to open the log {}", path.display()))?;
+                command.stdout(log.try_clone()?).stderr(log);
+            }
+        }
+
+        #[cfg(unix)]
+        if self.setsid {
+            use std::os::unix::process::CommandExt as _;
+
+            // SAFETY: setsid is async-signal-safe
+            unsafe {
+                command.pre_exec(|| {
+                    if libc::setsid() == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        Ok(command)
+    }
+}
+
+impl<Ctx, F> Action for CommandAction<Ctx, F>
+where
+    F: Fn(&Ctx, &str) -> Option<String> + Send,
+{
+    type Context = Ctx;
+
+    fn act(&self, ctx: &Self::Context) -> Result<()> {
+        let mut command = self.command(ctx)?;
+        let program = command.get_program().to_string_lossy().into_owned();
+
+        let mut child = command
+            .spawn()
+            .wrap_err_with(|| format!("failed to spawn {program}"))?;
+
+        if self.wait {
+            let status = child.wait()?;
+            ensure!(status.success(), "{program} exited with {status}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Fill the placeholders of `template`.
+fn expand(template: &str, fill: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let brace = &rest[i..];
+
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            out.push_str(&brace[..1]);
+            rest = &brace[2..];
+            continue;
+        }
+
+        let Some(end) = brace.find('}').filter(|_| brace.starts_with('{')) else {
+            bail!("unbalanced braces in {template:?}");
+        };
+
+        let placeholder = &brace[1..end];
+        let (name, quote) = match placeholder.strip_suffix(":q") {
+            Some(name) => (name, true),
+            None => (placeholder, false),
+        };
+        let value = fill(name).ok_or_else(|| eyre!("unknown placeholder {{{name}}}"))?;
+
+        if quote {
+            out.push_str(&shell_quote(&value));
+        } else {
+            out.push_str(&value);
+        }
+
+        rest = &brace[end + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Quote `value` with single quotes for POSIX shells.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(name: &str) -> Option<String> {
+        match name {
+            "name" => Some("it's $HOME".into()),
+            "empty" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand("--name={name}", fill).unwrap(), "--name=it's $HOME");
+        assert_eq!(expand("{name:q}", fill).unwrap(), r"'it'\''s $HOME'");
+        assert_eq!(expand("{{}}{empty}", fill).unwrap(), "{}");
+        assert!(expand("{unknown}", fill).is_err());
+        assert!(expand("{name", fill).is_err());
+        assert!(expand("name}", fill).is_err());
+    }
+
+    #[test]
+    fn test_command() {
+        let action =
+            |argv: &[&str]| CommandAction::new(argv.to_vec(), |_: &(), name| fill(name)).wait(true);
+
+        action(&["true"]).act(&()).unwrap();
+        assert!(action(&["sh", "-c", "exit 3"]).act(&()).is_err());
+        assert!(action(&["{unknown}"]).act(&()).is_err());
+
+        let log = std::env::temp_dir().join(format!("ltrait-extra-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&log);
+
+        action(&["sh", "-c", "printf %s {name:q} \"$FOO\"; pwd"])
+            .env("FOO", "bar")
+            .current_dir(Some("/".into()))
+            .output(Output::Log(log.clone()))
+            .act(&())
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "it's $HOMEbar/\n");
+        std::fs::remove_file(&log).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 use ltrait::{Filter, filter::FilterWrapper};
-use std::marker::PhantomData;
+use regex::{Regex, RegexBuilder};
+use std::{collections::HashMap, hash::Hash, marker::PhantomData, sync::Mutex};
 
 impl<T> FilterExt for T
 where
@@ -27,6 +28,27 @@ where
         ReversedFilter::new(self)
     }
 
+    /// Same as [`FilterExt::reverse`]
+    fn not(self) -> impl Filter<Context = <Self as Filter>::Context> {
+        ReversedFilter::new(self)
+    }
+
+    /// `other` is evaluated only if `self` is true
+    fn and<T>(self, other: T) -> impl Filter<Context = <Self as Filter>::Context>
+    where
+        T: Filter<Context = <Self as Filter>::Context>,
+    {
+        FilterAnd::new(self, other)
+    }
+
+    /// `other` is evaluated only if `self` is false
+    fn or<T>(self, other: T) -> impl Filter<Context = <Self as Filter>::Context>
+    where
+        T: Filter<Context = <Self as Filter>::Context>,
+    {
+        FilterOr::new(self, other)
+    }
+
     fn comb<Cushion, T, Ctx, F1, F2, F3>(
         self,
         transformer1: F1,
@@ -46,6 +68,118 @@ where
     }
 }
 
+pub struct FilterAnd<T1, T2, Ctx>
+where
+    T1: Filter<Context = Ctx>,
+    T2: Filter<Context = Ctx>,
+{
+    filter1: T1,
+    filter2: T2,
+}
+
+impl<T1, T2, Ctx> FilterAnd<T1, T2, Ctx>
+where
+    T1: Filter<Context = Ctx>,
+    T2: Filter<Context = Ctx>,
+{
+    pub fn new(filter1: T1, filter2: T2) -> Self {
+        Self { filter1, filter2 }
+    }
+}
+
+impl<T1, T2, Ctx> Filter for FilterAnd<T1, T2, Ctx>
+where
+    T1: Filter<Context = Ctx>,
+    T2: Filter<Context = Ctx>,
+{
+    type Context = Ctx;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        self.filter1.predicate(ctx, input) && self.filter2.predicate(ctx, input)
+    }
+}
+
+pub struct FilterOr<T1, T2, Ctx>
+where
+    T1: Filter<Context = Ctx>,
+    T2: Filter<Context = Ctx>,
+{
+    filter1: T1,
+    filter2: T2,
+}
+
+impl<T1, T2, Ctx> FilterOr<T1, T2, Ctx>
+where
+    T1: Filter<Context = Ctx>,
+    T2: Filter<Context = Ctx>,
+{
+    pub fn new(filter1: T1, filter2: T2) -> Self {
+        Self { filter1, filter2 }
+    }
+}
+
+impl<T1, T2, Ctx> Filter for FilterOr<T1, T2, Ctx>
+where
+    T1: Filter<Context = Ctx>,
+    T2: Filter<Context = Ctx>,
+{
+    type Context = Ctx;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        self.filter1.predicate(ctx, input) || self.filter2.predicate(ctx, input)
+    }
+}
+
+/// True if every filter is true (or there is no filter). Filters are evaluated in order until
+/// one is false.
+pub fn all_of<Ctx>(filters: Vec<Box<dyn Filter<Context = Ctx>>>) -> FilterAll<Ctx> {
+    FilterAll { filters }
+}
+
+/// True if any filter is true (false if there is no filter). Filters are evaluated in order
+/// until one is true.
+pub fn any_of<Ctx>(filters: Vec<Box<dyn Filter<Context = Ctx>>>) -> FilterAny<Ctx> {
+    FilterAny { filters }
+}
+
+pub struct FilterAll<Ctx> {
+    filters: Vec<Box<dyn Filter<Context = Ctx>>>,
+}
+
+impl<Ctx> FilterAll<Ctx> {
+    pub fn with(mut self, filter: impl Filter<Context = Ctx> + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+}
+
+impl<Ctx> Filter for FilterAll<Ctx> {
+    type Context = Ctx;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        self.filters.iter().all(|f| f.predicate(ctx, input))
+    }
+}
+
+pub struct FilterAny<Ctx> {
+    filters: Vec<Box<dyn Filter<Context = Ctx>>>,
+}
+
+impl<Ctx> FilterAny<Ctx> {
+    pub fn with(mut self, filter: impl Filter<Context = Ctx> + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+}
+
+impl<Ctx> Filter for FilterAny<Ctx> {
+    type Context = Ctx;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        self.filters.iter().any(|f| f.predicate(ctx, input))
+    }
+}
+
 pub struct FilterComb<Cushion, T1, T2, C1, C2, F1, F2, F3>
 where
     F1: Fn(&Cushion) -> C1 + Send,
@@ -194,3 +328,634 @@ where
         !self.filter.predicate(ctx, input)
     }
 }
+
+/// Filters with fzf's [extended-search mode](https://github.com/junegunn/fzf#search-syntax).
+///
+/// The input is split into space-separated terms (`\ ` for a literal space), all of which must
+/// match. Terms joined with `|` form a group, any of which must match.
+///
+/// | term      | matches                                 |
+/// | --------- | --------------------------------------- |
+/// | `sbtrkt`  | items that fuzzy-match `sbtrkt`         |
+/// | `'wild`   | items that include `wild`               |
+/// | `^music`  | items that start with `music`           |
+/// | `.mp3$`   | items that end with `.mp3`              |
+/// | `^foo$`   | items that are `foo`                    |
+/// | `!fire`   | items that don't include `fire`         |
+/// | `!^music` | items that don't start with `music`     |
+/// | `!.mp3$`  | items that don't end with `.mp3`        |
+///
+/// Terms are case-insensitive unless they contain an uppercase letter.
+/// Project the string to match with the transformer of `add_filter`.
+pub struct ExtendedQueryFilter<Ctx> {
+    // 毎回parseしないように最後のinputとparseした結果を持っておく
+    query: Mutex<Option<(String, Vec<Vec<Term>>)>>,
+
+    _ctx: PhantomData<fn(&Ctx)>,
+}
+
+impl<Ctx> Default for ExtendedQueryFilter<Ctx> {
+    fn default() -> Self {
+        Self {
+            query: Mutex::new(None),
+            _ctx: PhantomData,
+        }
+    }
+}
+
+impl<Ctx> Filter for ExtendedQueryFilter<Ctx>
+where
+    Ctx: AsRef<str>,
+{
+    type Context = Ctx;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        let mut query = self.query.lock().unwrap();
+
+        if query.as_ref().is_none_or(|(last, _)| last != input) {
+            *query = Some((input.into(), parse_extended_query(input)));
+        }
+
+        let (_, groups) = query.as_ref().unwrap();
+        let text = ctx.as_ref();
+
+        groups
+            .iter()
+            .all(|group| group.iter().any(|term| term.matches(text)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TermKind {
+    Fuzzy,
+    Exact,
+    Prefix,
+    Suffix,
+    Equal,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Term {
+    kind: TermKind,
+    negated: bool,
+    text: String,
+    case_sensitive: bool,
+}
+
+impl Term {
+    fn parse(mut token: &str) -> Option<Self> {
+        let negated = token.starts_with('!');
+        if negated {
+            token = &token[1..];
+        }
+
+        let kind = if let Some(rest) = token.strip_prefix('\'') {
+            token = rest;
+            TermKind::Exact
+        } else {
+            let prefix = token.strip_prefix('^');
+            let body = prefix.unwrap_or(token);
+            let suffix = body.strip_suffix('$').filter(|rest| !rest.is_empty());
+            token = suffix.unwrap_or(body);
+
+            match (prefix.is_some(), suffix.is_some()) {
+                (true, true) => TermKind::Equal,
+                (true, false) => TermKind::Prefix,
+                (false, true) => TermKind::Suffix,
+                // fzfと同じく否定はexact match
+                (false, false) if negated => TermKind::Exact,
+                (false, false) => TermKind::Fuzzy,
+            }
+        };
+
+        let case_sensitive = token.chars().any(char::is_uppercase);
+
+        (!token.is_empty()).then(|| Self {
+            kind,
+            negated,
+            text: if case_sensitive {
+                token.into()
+            } else {
+                token.to_lowercase()
+            },
+            case_sensitive,
+        })
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        let lowercase;
+        let text = if self.case_sensitive {
+            text
+        } else {
+            lowercase = text.to_lowercase();
+            &lowercase
+        };
+        let term = self.text.as_str();
+
+        let matched = match self.kind {
+            TermKind::Fuzzy => {
+                let mut chars = text.chars();
+                term.chars().all(|c| chars.any(|t| t == c))
+            }
+            TermKind::Exact => text.contains(term),
+            TermKind::Prefix => text.starts_with(term),
+            TermKind::Suffix => text.ends_with(term),
+            TermKind::Equal => text == term,
+        };
+
+        matched != self.negated
+    }
+}
+
+/// Parse `input` into groups of terms. Every group must match, and a group matches if any of
+/// its terms matches.
+fn parse_extended_query(input: &str) -> Vec<Vec<Term>> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(' ') => token.push(' '),
+                Some(next) => {
+                    token.push('\\');
+                    token.push(next);
+                }
+                None => token.push('\\'),
+            },
+            c if c.is_whitespace() => {
+                tokens.extend(Some(std::mem::take(&mut token)).filter(|t| !t.is_empty()))
+            }
+            c => token.push(c),
+        }
+    }
+    tokens.extend(Some(token).filter(|t| !t.is_empty()));
+
+    let mut groups: Vec<Vec<Term>> = vec![];
+    let mut or = false;
+
+    for token in tokens {
+        if token == "|" {
+            or = !groups.is_empty();
+            continue;
+        }
+
+        let Some(term) = Term::parse(&token) else {
+            continue;
+        };
+
+        match groups.last_mut() {
+            Some(group) if or => group.push(term),
+            _ => groups.push(vec![term]),
+        }
+        or = false;
+    }
+
+    groups
+}
+
+/// What [`RegexFilter`] does while the input is not a valid regex (e.g. `foo(` while typing).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidRegex {
+    /// Match the input literally
+    Literal,
+    /// Keep using the last valid regex (every item passes if there is none)
+    LastValid,
+}
+
+/// Treats the input as a regular expression and passes the items matching it.
+/// Every item passes if the input is empty.
+pub struct RegexFilter<Ctx> {
+    smart_case: bool,
+    whole_word: bool,
+    anchored: bool,
+    invalid: InvalidRegex,
+
+    // (input, そのinputで使うregex, 最後に有効だったregex)
+    state: Mutex<(Option<String>, Option<Regex>, Option<Regex>)>,
+
+    _ctx: PhantomData<fn(&Ctx)>,
+}
+
+impl<Ctx> Default for RegexFilter<Ctx> {
+    fn default() -> Self {
+        Self {
+            smart_case: true,
+            whole_word: false,
+            anchored: false,
+            invalid: InvalidRegex::Literal,
+            state: Mutex::new((None, None, None)),
+            _ctx: PhantomData,
+        }
+    }
+}
+
+impl<Ctx> RegexFilter<Ctx> {
+    /// Ignore case unless the input contains an uppercase letter (escapes like `\W` aside).
+    /// If false, the case is always respected.
+    /// The default value is true.
+    pub fn smart_case(mut self, flag: bool) -> Self {
+        self.smart_case = flag;
+        self
+    }
+
+    /// Match only at word boundaries.
+    /// The default value is false.
+    pub fn whole_word(mut self, flag: bool) -> Self {
+        self.whole_word = flag;
+        self
+    }
+
+    /// Match only at the start of the string.
+    /// The default value is false.
+    pub fn anchored(mut self, flag: bool) -> Self {
+        self.anchored = flag;
+        self
+    }
+
+    /// The default value is [`InvalidRegex::Literal`].
+    pub fn invalid(mut self, invalid: InvalidRegex) -> Self {
+        self.invalid = invalid;
+        self
+    }
+
+    fn build(&self, pattern: &str, case_insensitive: bool) -> Result<Regex, regex::Error> {
+        let mut pattern = format!("(?:{pattern})");
+        if self.whole_word {
+            pattern = format!(r"\b{pattern}\b");
+        }
+        if self.anchored {
+            pattern = format!("^{pattern}");
+        }
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+    }
+
+    /// The regex for `input`, or None if every item passes.
+    fn compile(&self, input: &str, last_valid: &mut Option<Regex>) -> Option<Regex> {
+        if input.is_empty() {
+            return None;
+        }
+
+        let case_insensitive = self.smart_case && !has_uppercase(input);
+
+        match self.build(input, case_insensitive) {
+            Ok(regex) => {
+                *last_valid = Some(regex.clone());
+                Some(regex)
+            }
+            Err(_) => match self.invalid {
+                InvalidRegex::Literal => self.build(&regex::escape(input), case_insensitive).ok(),
+                InvalidRegex::LastValid => last_valid.clone(),
+            },
+        }
+    }
+}
+
+/// Whether `pattern` has an uppercase letter that isn't a part of an escape like `\W`.
+fn has_uppercase(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+
+    false
+}
+
+impl<Ctx> Filter for RegexFilter<Ctx>
+where
+    Ctx: AsRef<str>,
+{
+    type Context = Ctx;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let (last_input, regex, last_valid) = &mut *state;
+
+        if last_input.as_deref() != Some(input) {
+            *regex = self.compile(input, last_valid);
+            *last_input = Some(input.into());
+        }
+
+        regex
+            .as_ref()
+            .is_none_or(|regex| regex.is_match(ctx.as_ref()))
+    }
+}
+
+/// Passes only one item per key, e.g. to show a program once when it comes from desktop entries
+/// of several XDG dirs, `PATH` and the history.
+///
+/// Without [`DedupFilter::priority`], the first item of each key passes, so put the sources in the
+/// order of preference. The bookkeeping is reset when the input changes, so the items must not be
+/// filtered again for the same input (ltrait-ui-tui only filters them again when the query
+/// changes).
+///
+/// A filter can't take back an item that has already passed, so the highest priority is learned
+/// from the items seen for the previous input. While the items for the first input are streamed, a
+/// lower priority item seen before a higher priority one of the same key passes as well.
+/// Add this filter before the others so that it sees the same items for every input.
+///
+/// ```
+/// # use ltrait::Filter;
+/// use ltrait_extra::filter::DedupFilter;
+///
+/// // (name, priority)
+/// let dedup = DedupFilter::new(|item: &(&str, u8)| item.0).priority(|item| item.1);
+///
+/// for input in ["", "f"] {
+///     let passed: Vec<_> = [("foot", 0), ("firefox", 0), ("foot", 1)]
+///         .into_iter()
+///         .filter(|item| dedup.predicate(item, input))
+///         .collect();
+///
+///     if input == "f" {
+///         assert_eq!(passed, [("firefox", 0), ("foot", 1)]);
+///     }
+/// }
+/// ```
+pub struct DedupFilter<Ctx, K, P, FK, FP>
+where
+    FK: Fn(&Ctx) -> K + Send,
+    FP: Fn(&Ctx) -> P + Send,
+{
+    key: FK,
+    priority: FP,
+
+    state: Mutex<DedupState<K, P>>,
+
+    _ctx: PhantomData<fn(&Ctx)>,
+}
+
+struct DedupState<K, P> {
+    input: Option<String>,
+    // このinputで通したkeyとそのpriority
+    passed: HashMap<K, P>,
+    // keyごとの最大のpriority (このinputと前のinput)
+    best: HashMap<K, P>,
+    prev_best: HashMap<K, P>,
+}
+
+impl<K, P> Default for DedupState<K, P> {
+    fn default() -> Self {
+        Self {
+            input: None,
+            passed: HashMap::new(),
+            best: HashMap::new(),
+            prev_best: HashMap::new(),
+        }
+    }
+}
+
+impl<Ctx, K, FK> DedupFilter<Ctx, K, (), FK, fn(&Ctx)>
+where
+    FK: Fn(&Ctx) -> K + Send,
+{
+    pub fn new(key: FK) -> Self {
+        Self {
+            key,
+            priority: |_| (),
+            state: Mutex::default(),
+            _ctx: PhantomData,
+        }
+    }
+}
+
+impl<Ctx, K, P, FK, FP> DedupFilter<Ctx, K, P, FK, FP>
+where
+    FK: Fn(&Ctx) -> K + Send,
+    FP: Fn(&Ctx) -> P + Send,
+{
+    /// Pass the item of the highest priority in each key instead of the first one.
+    /// Among the items of the same priority, the first one passes.
+    pub fn priority<P2, FP2>(self, priority: FP2) -> DedupFilter<Ctx, K, P2, FK, FP2>
+    where
+        FP2: Fn(&Ctx) -> P2 + Send,
+    {
+        DedupFilter {
+            key: self.key,
+            priority,
+            state: Mutex::default(),
+            _ctx: PhantomData,
+        }
+    }
+}
+
+impl<Ctx, K, P, FK, FP> Filter for DedupFilter<Ctx, K, P, FK, FP>
+where
+    K: Hash + Eq + Clone + Send,
+    P: Ord + Clone + Send,
+    FK: Fn(&Ctx) -> K + Send,
+    FP: Fn(&Ctx) -> P + Send,
+{
+    type Context = Ctx;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        if state.input.as_deref() != Some(input) {
+            // 前のinputで見なかったkeyは忘れる(generatorのitemなどは毎回変わるので)
+            state.prev_best = std::mem::take(&mut state.best);
+            state.passed.clear();
+            state.input = Some(input.into());
+        }
+
+        let key = (self.key)(ctx);
+        let priority = (self.priority)(ctx);
+
+        let best = state
+            .best
+            .entry(key.clone())
+            .and_modify(|best| {
+                if *best < priority {
+                    *best = priority.clone();
+                }
+            })
+            .or_insert_with(|| priority.clone())
+            .clone();
+        let best = match state.prev_best.get(&key) {
+            Some(prev) if *prev > best => prev.clone(),
+            _ => best,
+        };
+
+        // 先に低いpriorityのitemを通していた場合は、より高いitemも通す(取り消せないので)
+        if priority < best
+            || state
+                .passed
+                .get(&key)
+                .is_some_and(|passed| *passed >= priority)
+        {
+            return false;
+        }
+
+        state.passed.insert(key, priority);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ltrait::filter::ClosureFilter;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLED: AtomicUsize = AtomicUsize::new(0);
+
+    fn is(c: char) -> impl Filter<Context = String> {
+        ClosureFilter::new(move |ctx: &String, _: &str| ctx.contains(c))
+    }
+
+    fn counted(result: bool) -> impl Filter<Context = String> {
+        ClosureFilter::new(move |_: &String, _: &str| {
+            CALLED.fetch_add(1, Ordering::SeqCst);
+            result
+        })
+    }
+
+    #[test]
+    fn test_combinators() {
+        // a and (b or not c)
+        let filter = is('a').and(is('b').or(is('c').not()));
+
+        assert!(filter.predicate(&"ab".into(), ""));
+        assert!(filter.predicate(&"a".into(), ""));
+        assert!(!filter.predicate(&"ac".into(), ""));
+        assert!(!filter.predicate(&"b".into(), ""));
+
+        assert!(all_of(vec![]).predicate(&String::new(), ""));
+        assert!(!any_of(vec![]).predicate(&String::new(), ""));
+        assert!(
+            any_of(vec![Box::new(is('x'))])
+                .with(is('y'))
+                .predicate(&"y".into(), "")
+        );
+        assert!(
+            !all_of(vec![Box::new(is('x'))])
+                .with(is('y'))
+                .predicate(&"y".into(), "")
+        );
+
+        // short circuit
+        CALLED.store(0, Ordering::SeqCst);
+        assert!(!is('a').and(counted(true)).predicate(&"b".into(), ""));
+        assert!(is('a').or(counted(true)).predicate(&"a".into(), ""));
+        assert!(
+            !all_of(vec![Box::new(counted(false)), Box::new(counted(false))])
+                .predicate(&String::new(), "")
+        );
+        assert!(
+            any_of(vec![Box::new(counted(true)), Box::new(counted(true))])
+                .predicate(&String::new(), "")
+        );
+        assert_eq!(CALLED.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_extended_query() {
+        let filter = ExtendedQueryFilter::<String>::default();
+        let matches = |input: &str, text: &str| filter.predicate(&text.into(), input);
+
+        assert!(matches("sbtrkt", "subtracket"));
+        assert!(!matches("sbtrkt", "tracket"));
+        assert!(matches("'wild", "a wildcard"));
+        assert!(!matches("'wlid", "a wildcard"));
+        assert!(matches("^music", "music/a.mp3"));
+        assert!(!matches("^music", "my music"));
+        assert!(matches(".mp3$", "music/a.mp3"));
+        assert!(matches("^foo$", "foo"));
+        assert!(!matches("^foo$", "foobar"));
+        assert!(matches("!fire", "foot"));
+        assert!(!matches("!fire", "firefox"));
+        assert!(!matches("!^fi !ox$", "fi"));
+        assert!(!matches("!^fi !ox$", "box"));
+        assert!(matches("!^fi !ox$", "foxfi"));
+
+        // smart case
+        assert!(matches("FoX", "FireFoX"));
+        assert!(!matches("FoX", "firefox"));
+        assert!(matches("fox", "FireFoX"));
+
+        // `|` and escaped spaces
+        assert!(matches("^core go$ | rb$ | py$", "core/main.rb"));
+        assert!(!matches("^core go$ | rb$ | py$", "core/main.c"));
+        assert!(!matches("^core go$ | rb$ | py$", "lib/main.rb"));
+        assert!(matches("'a\\ b", "a b"));
+        assert!(!matches("'a\\ b", "ab"));
+
+        // empty terms match everything
+        assert!(matches("", "anything"));
+        assert!(matches("! ^ | '", "anything"));
+    }
+
+    #[test]
+    fn test_regex() {
+        let matches = |filter: &RegexFilter<String>, input: &str, text: &str| {
+            filter.predicate(&text.into(), input)
+        };
+
+        let filter = RegexFilter::default();
+        assert!(matches(&filter, "fire.*x", "Firefox"));
+        assert!(!matches(&filter, "Fire.*X", "Firefox"));
+        // escapes are not uppercase letters
+        assert!(matches(&filter, r"fire\Wfox", "FIRE FOX"));
+        assert!(matches(&filter, "", "anything"));
+        // invalid regexes are matched literally
+        assert!(matches(&filter, "(a", "b(a"));
+        assert!(!matches(&filter, "(a", "ba"));
+
+        let filter = RegexFilter::default().whole_word(true).anchored(true);
+        assert!(matches(&filter, "foo", "foo bar"));
+        assert!(!matches(&filter, "foo", "foobar"));
+        assert!(!matches(&filter, "bar", "foo bar"));
+
+        let filter = RegexFilter::default()
+            .smart_case(false)
+            .invalid(InvalidRegex::LastValid);
+        assert!(matches(&filter, "(a", "anything"));
+        assert!(matches(&filter, "b|c", "c"));
+        assert!(matches(&filter, "b|c(", "c"));
+        assert!(!matches(&filter, "b|c(", "a"));
+        assert!(!matches(&filter, "B", "b"));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let items = [("foot", 1), ("firefox", 0), ("foot", 2), ("foot", 2)];
+        let passed = |filter: &dyn Filter<Context = (&str, u8)>, input| -> Vec<_> {
+            items
+                .iter()
+                .filter(|item| filter.predicate(item, input))
+                .copied()
+                .collect()
+        };
+
+        let first = DedupFilter::new(|item: &(&str, u8)| item.0);
+        assert_eq!(passed(&first, ""), [("foot", 1), ("firefox", 0)]);
+        // reset on a new input
+        assert_eq!(passed(&first, "f"), [("foot", 1), ("firefox", 0)]);
+
+        let highest = DedupFilter::new(|item: &(&str, u8)| item.0).priority(|item| item.1);
+        // the priority of the third item isn't known yet
+        assert_eq!(
+            passed(&highest, ""),
+            [("foot", 1), ("firefox", 0), ("foot", 2)]
+        );
+        assert_eq!(passed(&highest, "f"), [("firefox", 0), ("foot", 2)]);
+
+        // the first item comes again in the same pass
+        let dedup = DedupFilter::new(|item: &&str| *item);
+        let passed: Vec<_> = ["a", "b", "a", "b"]
+            .into_iter()
+            .filter(|item| dedup.predicate(item, "q"))
+            .collect();
+        assert_eq!(passed, ["a", "b"]);
+    }
+}
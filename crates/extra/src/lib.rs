@@ -1,4 +1,8 @@
 pub mod action;
+pub mod audit;
+pub mod clipboard;
+pub mod command;
 pub mod filter;
+pub mod router;
 pub mod scorer;
 pub mod sorter;
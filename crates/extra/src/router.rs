@@ -0,0 +1,277 @@
+//! Route components by a prefix of the input, so that one launcher can host many modes
+//! (e.g. `>` for commands, `/` for files and `=` for a calculator).
+//!
+//! Filters run on every item, so limit each routed filter to the items of its component with
+//! [`FilterExt::to_if`](crate::filter::FilterExt::to_if). Otherwise the filter of `>` would also
+//! filter the apps, and the others filter would hide the commands in the `>` mode.
+//!
+//! ```
+//! # use ltrait::filter::ClosureFilter;
+//! use ltrait_extra::{filter::FilterExt as _, router::PrefixRouter};
+//!
+//! enum Item {
+//!     App(String),
+//!     // e.g. generated by `router.generator(">", ..)`
+//!     Command(String),
+//! }
+//!
+//! let name = |item: &Item| match item {
+//!     Item::App(name) | Item::Command(name) => name.clone(),
+//! };
+//!
+//! let router = PrefixRouter::default()
+//!     // only the components of the mode are active
+//!     .mode(">", true)
+//!     // the components of the mode are active as well as the others
+//!     .mode("=", false);
+//!
+//! // active only if the input starts with `>`, and sees the input without `>`
+//! let commands = router
+//!     .filter(">", ClosureFilter::new(|c: &String, input| c.starts_with(input)))
+//!     .to_if(|item: &Item| matches!(item, Item::Command(_)), name);
+//! // active unless an exclusive mode is active, and sees the whole input
+//! let apps = router
+//!     .others_filter(ClosureFilter::new(|c: &String, input| c.contains(input)))
+//!     .to_if(|item: &Item| matches!(item, Item::App(_)), name);
+//! ```
+
+use ltrait::{Filter, Generator, Sorter, async_trait::async_trait};
+
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+struct Mode {
+    prefix: String,
+    exclusive: bool,
+}
+
+/// Declares the modes and wraps components so that they're active only in some modes.
+///
+/// If several prefixes match the input, the longest one wins (e.g. `=k ` over `=`).
+/// Clones share the modes declared so far.
+#[derive(Debug, Clone, Default)]
+pub struct PrefixRouter {
+    modes: Arc<Vec<Mode>>,
+}
+
+#[derive(Debug, Clone)]
+enum Route {
+    Mode(String),
+    Others,
+}
+
+impl PrefixRouter {
+    /// If `exclusive` is true, the components wrapped with `others_*` are inactive in this mode.
+    pub fn mode(mut self, prefix: impl Into<String>, exclusive: bool) -> Self {
+        Arc::make_mut(&mut self.modes).push(Mode {
+            prefix: prefix.into(),
+            exclusive,
+        });
+        self
+    }
+
+    /// The mode of the longest prefix of `input`, if any.
+    fn active(&self, input: &str) -> Option<&Mode> {
+        self.modes
+            .iter()
+            .filter(|mode| input.starts_with(&mode.prefix))
+            .max_by_key(|mode| mode.prefix.len())
+    }
+
+    /// The input seen by a component of `route`, or None if the component is inactive.
+    fn route<'a>(&self, route: &Route, input: &'a str) -> Option<&'a str> {
+        let active = self.active(input);
+
+        match route {
+            // 宣言されていないprefixでもstarts_withなら有効にする(より長いmodeがある場合は除く)
+            Route::Mode(prefix) => input
+                .strip_prefix(prefix.as_str())
+                .filter(|_| active.is_none_or(|mode| mode.prefix.len() <= prefix.len())),
+            Route::Others => (!active.is_some_and(|mode| mode.exclusive)).then_some(input),
+        }
+    }
+
+    fn routed<T>(&self, route: Route, inner: T) -> Routed<T> {
+        Routed {
+            inner,
+            route,
+            router: self.clone(),
+        }
+    }
+
+    /// Active only in the mode of `prefix`. When inactive, every item passes.
+    /// Limit it to the items of the mode (see the [module docs](self)).
+    pub fn filter<T: Filter>(&self, prefix: impl Into<String>, filter: T) -> Routed<T> {
+        self.routed(Route::Mode(prefix.into()), filter)
+    }
+
+    /// Active only in the mode of `prefix`. When inactive, every item is equal.
+    pub fn sorter<T: Sorter>(&self, prefix: impl Into<String>, sorter: T) -> Routed<T> {
+        self.routed(Route::Mode(prefix.into()), sorter)
+    }
+
+    /// Active only in the mode of `prefix`. When inactive, nothing is generated.
+    pub fn generator<T: Generator>(&self, prefix: impl Into<String>, generator: T) -> Routed<T> {
+        self.routed(Route::Mode(prefix.into()), generator)
+    }
+
+    /// Active unless an exclusive mode is active. When inactive, no item passes, so limit it to
+    /// the items of the other components (see the [module docs](self)).
+    pub fn others_filter<T: Filter>(&self, filter: T) -> Routed<T> {
+        self.routed(Route::Others, filter)
+    }
+
+    /// Active unless an exclusive mode is active. When inactive, every item is equal.
+    pub fn others_sorter<T: Sorter>(&self, sorter: T) -> Routed<T> {
+        self.routed(Route::Others, sorter)
+    }
+
+    /// Active unless an exclusive mode is active. When inactive, nothing is generated.
+    pub fn others_generator<T: Generator>(&self, generator: T) -> Routed<T> {
+        self.routed(Route::Others, generator)
+    }
+}
+
+/// A filter, sorter or generator wrapped by [`PrefixRouter`].
+pub struct Routed<T> {
+    inner: T,
+    route: Route,
+    router: PrefixRouter,
+}
+
+impl<T> Filter for Routed<T>
+where
+    T: Filter,
+{
+    type Context = T::Context;
+
+    fn predicate(&self, ctx: &Self::Context, input: &str) -> bool {
+        match self.router.route(&self.route, input) {
+            Some(input) => self.inner.predicate(ctx, input),
+            // modeのfilterは関係ないitemを消さないように、それ以外は排他的なmodeでitemを出さないように
+            None => matches!(self.route, Route::Mode(_)),
+        }
+    }
+}
+
+impl<T> Sorter for Routed<T>
+where
+    T: Sorter,
+{
+    type Context = T::Context;
+
+    fn compare(&self, lhs: &Self::Context, rhs: &Self::Context, input: &str) -> std::cmp::Ordering {
+        match self.router.route(&self.route, input) {
+            Some(input) => self.inner.compare(lhs, rhs, input),
+            None => std::cmp::Ordering::Equal,
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Generator for Routed<T>
+where
+    T: Generator,
+{
+    type Item = T::Item;
+
+    async fn generate(&self, input: &str) -> Vec<Self::Item> {
+        match self.router.route(&self.route, input) {
+            Some(input) => self.inner.generate(input).await,
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ltrait::filter::ClosureFilter;
+
+    #[test]
+    fn test_route() {
+        let router = PrefixRouter::default()
+            .mode(">", true)
+            .mode("=", false)
+            .mode("=k ", false);
+
+        let seen = |route: Route, input| router.route(&route, input);
+        let mode = |prefix: &str| Route::Mode(prefix.into());
+
+        assert_eq!(seen(mode(">"), ">ls"), Some("ls"));
+        assert_eq!(seen(mode(">"), "ls"), None);
+        assert_eq!(seen(Route::Others, ">ls"), None);
+        assert_eq!(seen(Route::Others, "ls"), Some("ls"));
+
+        // the longest prefix wins
+        assert_eq!(seen(mode("="), "= 1"), Some(" 1"));
+        assert_eq!(seen(mode("="), "=k 1"), None);
+        assert_eq!(seen(mode("=k "), "=k 1"), Some("1"));
+        assert_eq!(seen(Route::Others, "=k 1"), Some("=k 1"));
+
+        // undeclared prefixes are matched as is
+        assert_eq!(seen(mode("/"), "/etc"), Some("etc"));
+    }
+
+    #[test]
+    fn test_filter() {
+        let router = PrefixRouter::default().mode(">", true);
+        let contains = || ClosureFilter::new(|c: &String, input: &str| c.contains(input));
+
+        let commands = router.filter(">", contains());
+        let apps = router.others_filter(contains());
+
+        assert!(commands.predicate(&"ls".into(), ">l"));
+        assert!(!commands.predicate(&"ls".into(), ">x"));
+        assert!(commands.predicate(&"firefox".into(), "fire"));
+
+        assert!(apps.predicate(&"firefox".into(), "fire"));
+        assert!(!apps.predicate(&"firefox".into(), ">"));
+    }
+
+    #[test]
+    fn test_filters_together() {
+        use crate::filter::{FilterExt as _, all_of};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum Item {
+            App(&'static str),
+            Command(&'static str),
+        }
+
+        let name = |item: &Item| match item {
+            Item::App(name) | Item::Command(name) => name.to_string(),
+        };
+
+        let router = PrefixRouter::default().mode(">", true);
+        let commands = router
+            .filter(
+                ">",
+                ClosureFilter::new(|c: &String, input: &str| c.starts_with(input)),
+            )
+            .to_if(|item: &Item| matches!(item, Item::Command(_)), name);
+        let apps = router
+            .others_filter(ClosureFilter::new(|c: &String, input: &str| {
+                c.contains(input)
+            }))
+            .to_if(|item: &Item| matches!(item, Item::App(_)), name);
+
+        // filter_andのbatcherと同じように全部のfilterを全部のitemに適用する
+        let filter = all_of(vec![Box::new(commands), Box::new(apps)]);
+        let passed = |items: &[Item], input| -> Vec<_> {
+            items
+                .iter()
+                .filter(|item| filter.predicate(item, input))
+                .cloned()
+                .collect()
+        };
+
+        let items = [
+            Item::App("firefox"),
+            Item::App("slack"),
+            Item::Command("ls"),
+        ];
+        assert_eq!(passed(&items, ">l"), [Item::Command("ls")]);
+        assert_eq!(passed(&items[..2], "fire"), [Item::App("firefox")]);
+    }
+}
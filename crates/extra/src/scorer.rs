@@ -18,10 +18,269 @@ where {
     {
         ScorerFilter(self, predicate)
     }
+
+    fn map<F>(self, f: F) -> impl Scorer<Context = Self::Context> + Send
+    where
+        F: Fn(u32) -> u32 + Send,
+    {
+        ScorerMap::new(self, f)
+    }
+
+    /// Multiply the score by `factor`, rounded and saturated to `u32`
+    fn scale(self, factor: f64) -> impl Scorer<Context = Self::Context> + Send {
+        self.map(move |score| (score as f64 * factor).round() as u32)
+    }
+
+    /// Add `delta` to the score, saturated to `u32`
+    fn offset(self, delta: i64) -> impl Scorer<Context = Self::Context> + Send {
+        self.map(move |score| {
+            (score as i64)
+                .saturating_add(delta)
+                .clamp(0, u32::MAX as i64) as u32
+        })
+    }
+
+    fn clamp(self, min: u32, max: u32) -> impl Scorer<Context = Self::Context> + Send {
+        self.map(move |score| score.clamp(min, max))
+    }
+
+    /// Saturated at `u32::MAX`
+    fn sum<T>(self, other: T) -> impl Scorer<Context = Self::Context> + Send
+    where
+        T: Scorer<Context = Self::Context> + Send,
+    {
+        ScorerComb::new(self, other, u32::saturating_add)
+    }
+
+    fn max<T>(self, other: T) -> impl Scorer<Context = Self::Context> + Send
+    where
+        T: Scorer<Context = Self::Context> + Send,
+    {
+        ScorerComb::new(self, other, std::cmp::max)
+    }
+
+    fn min<T>(self, other: T) -> impl Scorer<Context = Self::Context> + Send
+    where
+        T: Scorer<Context = Self::Context> + Send,
+    {
+        ScorerComb::new(self, other, std::cmp::min)
+    }
+
+    /// Compute the score of each context once per input. See [`CachedScorer`]
+    fn cached(self) -> CachedScorer<Self>
+    where
+        Self: Sync,
+        Self::Context: Hash + Eq + Clone + Send,
+    {
+        CachedScorer::new(self)
+    }
+
+    /// Score `Cushion` by transforming it to the context of `self`
+    fn wrap<Cushion, TransF>(self, transformer: TransF) -> impl Scorer<Context = Cushion> + Send
+    where
+        TransF: Fn(&Cushion) -> Self::Context + Send,
+    {
+        ScorerWrapper::new(self, transformer)
+    }
 }
 
 use ltrait::Filter;
 use ltrait::Sorter;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+type Cache<Ctx> = Arc<Mutex<(String, HashMap<Ctx, u32>)>>;
+
+/// Memoizes the scores of the inner scorer for the current input, so that sorting scores each
+/// item once instead of twice per comparison. The cache is cleared when the input changes.
+///
+/// Clones share the cache, so a sorter and a filter made from the same scorer score each item
+/// only once. Contexts that are equal share a score.
+///
+/// ```
+/// # use ltrait_extra::scorer::{Scorer, ScorerExt as _};
+/// # struct Len;
+/// # impl Scorer for Len {
+/// #     type Context = String;
+/// #     fn predicate_score(&self, ctx: &String, _: &str) -> u32 { ctx.len() as u32 }
+/// # }
+/// let scorer = Len.cached();
+///
+/// let sorter = scorer.clone().into_sorter();
+/// let filter = scorer.into_filter(|score| score > 0);
+/// ```
+pub struct CachedScorer<T>
+where
+    T: Scorer,
+{
+    scorer: Arc<T>,
+    cache: Cache<T::Context>,
+}
+
+impl<T> Clone for CachedScorer<T>
+where
+    T: Scorer,
+{
+    fn clone(&self) -> Self {
+        Self {
+            scorer: self.scorer.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<T> CachedScorer<T>
+where
+    T: Scorer,
+    T::Context: Hash + Eq + Clone,
+{
+    pub fn new(scorer: T) -> Self {
+        Self {
+            scorer: Arc::new(scorer),
+            cache: Arc::new(Mutex::new((String::new(), HashMap::new()))),
+        }
+    }
+}
+
+impl<T> Scorer for CachedScorer<T>
+where
+    T: Scorer,
+    T::Context: Hash + Eq + Clone,
+{
+    type Context = T::Context;
+
+    fn predicate_score(&self, ctx: &Self::Context, input: &str) -> u32 {
+        let mut cache = self.cache.lock().unwrap();
+        let (cached_input, scores) = &mut *cache;
+
+        if cached_input != input {
+            *cached_input = input.into();
+            scores.clear();
+        }
+
+        if let Some(score) = scores.get(ctx) {
+            return *score;
+        }
+
+        let score = self.scorer.predicate_score(ctx, input);
+        scores.insert(ctx.clone(), score);
+        score
+    }
+}
+
+pub struct ScorerMap<T, F>
+where
+    T: Scorer,
+    F: Fn(u32) -> u32,
+{
+    scorer: T,
+    f: F,
+}
+
+impl<T, F> ScorerMap<T, F>
+where
+    T: Scorer,
+    F: Fn(u32) -> u32,
+{
+    pub fn new(scorer: T, f: F) -> Self {
+        Self { scorer, f }
+    }
+}
+
+impl<T, F> Scorer for ScorerMap<T, F>
+where
+    T: Scorer,
+    F: Fn(u32) -> u32,
+{
+    type Context = T::Context;
+
+    fn predicate_score(&self, ctx: &Self::Context, input: &str) -> u32 {
+        (self.f)(self.scorer.predicate_score(ctx, input))
+    }
+}
+
+pub struct ScorerComb<T1, T2, F>
+where
+    T1: Scorer,
+    T2: Scorer<Context = T1::Context>,
+    F: Fn(u32, u32) -> u32,
+{
+    scorer1: T1,
+    scorer2: T2,
+    f: F,
+}
+
+impl<T1, T2, F> ScorerComb<T1, T2, F>
+where
+    T1: Scorer,
+    T2: Scorer<Context = T1::Context>,
+    F: Fn(u32, u32) -> u32,
+{
+    pub fn new(scorer1: T1, scorer2: T2, f: F) -> Self {
+        Self {
+            scorer1,
+            scorer2,
+            f,
+        }
+    }
+}
+
+impl<T1, T2, F> Scorer for ScorerComb<T1, T2, F>
+where
+    T1: Scorer,
+    T2: Scorer<Context = T1::Context>,
+    F: Fn(u32, u32) -> u32,
+{
+    type Context = T1::Context;
+
+    fn predicate_score(&self, ctx: &Self::Context, input: &str) -> u32 {
+        (self.f)(
+            self.scorer1.predicate_score(ctx, input),
+            self.scorer2.predicate_score(ctx, input),
+        )
+    }
+}
+
+pub struct ScorerWrapper<T, F, Cushion>
+where
+    T: Scorer,
+    F: Fn(&Cushion) -> T::Context,
+{
+    scorer: T,
+    transformer: F,
+
+    _cushion: PhantomData<fn(&Cushion)>,
+}
+
+impl<T, F, Cushion> ScorerWrapper<T, F, Cushion>
+where
+    T: Scorer,
+    F: Fn(&Cushion) -> T::Context,
+{
+    pub fn new(scorer: T, transformer: F) -> Self {
+        Self {
+            scorer,
+            transformer,
+            _cushion: PhantomData,
+        }
+    }
+}
+
+impl<T, F, Cushion> Scorer for ScorerWrapper<T, F, Cushion>
+where
+    T: Scorer,
+    F: Fn(&Cushion) -> T::Context,
+{
+    type Context = Cushion;
+
+    fn predicate_score(&self, ctx: &Self::Context, input: &str) -> u32 {
+        self.scorer.predicate_score(&(self.transformer)(ctx), input)
+    }
+}
 
 pub struct ScorerSorter<C, T>(pub T)
 where
@@ -75,3 +334,129 @@ where
         (self.1)(self.0.predicate_score(ctx, input))
     }
 }
+
+type WeightedScore<Cushion> = Box<dyn Fn(&Cushion, &str) -> f64 + Send>;
+
+/// Sorts by the weighted sum of the scores of several scorers (higher is greater, like
+/// [`ScorerSorter`]), so that e.g. a good match can beat a frequently used entry and vice versa.
+///
+/// ```
+/// # use ltrait_extra::scorer::{Scorer, WeightedSorter};
+/// # struct Len;
+/// # impl Scorer for Len {
+/// #     type Context = String;
+/// #     fn predicate_score(&self, ctx: &String, _: &str) -> u32 { ctx.len() as u32 }
+/// # }
+/// struct Item {
+///     name: String,
+///     description: String,
+/// }
+///
+/// let sorter = WeightedSorter::default()
+///     .scorer(Len, 1.0, |item: &Item| item.name.clone())
+///     .scorer(Len, 0.3, |item: &Item| item.description.clone());
+/// ```
+pub struct WeightedSorter<Cushion> {
+    scorers: Vec<WeightedScore<Cushion>>,
+}
+
+impl<Cushion> Default for WeightedSorter<Cushion> {
+    fn default() -> Self {
+        Self { scorers: vec![] }
+    }
+}
+
+impl<Cushion> WeightedSorter<Cushion> {
+    pub fn scorer<T, TransF>(mut self, scorer: T, weight: f64, transformer: TransF) -> Self
+    where
+        T: Scorer + Send + 'static,
+        TransF: Fn(&Cushion) -> <T as Scorer>::Context + Send + 'static,
+    {
+        self.scorers.push(Box::new(move |cushion, input| {
+            scorer.predicate_score(&transformer(cushion), input) as f64 * weight
+        }));
+        self
+    }
+
+    fn score(&self, cushion: &Cushion, input: &str) -> f64 {
+        self.scorers.iter().map(|score| score(cushion, input)).sum()
+    }
+}
+
+impl<Cushion> Sorter for WeightedSorter<Cushion> {
+    type Context = Cushion;
+
+    fn compare(&self, lhs: &Self::Context, rhs: &Self::Context, input: &str) -> std::cmp::Ordering {
+        self.score(lhs, input).total_cmp(&self.score(rhs, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Len;
+
+    impl Scorer for Len {
+        type Context = String;
+
+        fn predicate_score(&self, ctx: &Self::Context, _: &str) -> u32 {
+            ctx.len() as u32
+        }
+    }
+
+    #[test]
+    fn test_combinators() {
+        let ctx = "abcd".to_string();
+
+        assert_eq!(Len.scale(2.5).predicate_score(&ctx, ""), 10);
+        assert_eq!(Len.offset(-10).predicate_score(&ctx, ""), 0);
+        assert_eq!(Len.offset(1).predicate_score(&ctx, ""), 5);
+        assert_eq!(Len.clamp(5, 8).predicate_score(&ctx, ""), 5);
+        assert_eq!(Len.map(|s| s * s).predicate_score(&ctx, ""), 16);
+
+        assert_eq!(Len.sum(Len.scale(0.5)).predicate_score(&ctx, ""), 6);
+        assert_eq!(Len.max(Len.offset(1)).predicate_score(&ctx, ""), 5);
+        assert_eq!(Len.min(Len.offset(1)).predicate_score(&ctx, ""), 4);
+        assert_eq!(
+            Len.map(|_| u32::MAX).sum(Len).predicate_score(&ctx, ""),
+            u32::MAX
+        );
+
+        let wrapped = Len.wrap(|n: &usize| "a".repeat(*n));
+        assert_eq!(wrapped.predicate_score(&3, ""), 3);
+    }
+
+    #[test]
+    fn test_cached() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static CALLED: AtomicU32 = AtomicU32::new(0);
+
+        struct Counted;
+
+        impl Scorer for Counted {
+            type Context = String;
+
+            fn predicate_score(&self, ctx: &Self::Context, input: &str) -> u32 {
+                CALLED.fetch_add(1, Ordering::SeqCst);
+                (ctx.len() + input.len()) as u32
+            }
+        }
+
+        let scorer = Counted.cached();
+        let sorter = scorer.clone().into_sorter();
+        let filter = scorer.into_filter(|score| score > 3);
+        let (a, b) = ("ab".to_string(), "abc".to_string());
+
+        assert_eq!(sorter.compare(&a, &b, "x"), std::cmp::Ordering::Less);
+        assert_eq!(sorter.compare(&b, &a, "x"), std::cmp::Ordering::Greater);
+        assert!(filter.predicate(&b, "x"));
+        assert!(!filter.predicate(&a, "x"));
+        assert_eq!(CALLED.load(Ordering::SeqCst), 2);
+
+        // the cache is cleared when the input changes
+        assert!(filter.predicate(&a, "xy"));
+        assert_eq!(CALLED.load(Ordering::SeqCst), 3);
+    }
+}
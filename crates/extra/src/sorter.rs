@@ -4,7 +4,13 @@ use std::marker::PhantomData;
 impl<T> SorterExt for T where T: Sorter {}
 
 pub trait SorterExt: Sorter {
-    fn to_if<Cushion, F, TransF>(self, f: F, transformer: TransF) -> impl Sorter<Context = Cushion>
+    /// Apply `self` only to the pairs whose items both satisfy `f`. The satisfying items are
+    /// ranked first (see [`SorterIf::mixed`]).
+    fn to_if<Cushion, F, TransF>(
+        self,
+        f: F,
+        transformer: TransF,
+    ) -> SorterIf<SorterWrapper<<Self as Sorter>::Context, Self, TransF, Cushion>, Cushion, F>
     // Wrapもされる
     where
         Self: Sized,
@@ -23,8 +29,66 @@ pub trait SorterExt: Sorter {
     {
         ReversedSorter::new(self)
     }
+
+    /// `other` only breaks the ties of `self`
+    fn then_by<T>(self, other: T) -> impl Sorter<Context = <Self as Sorter>::Context>
+    where
+        Self: Sized,
+        T: Sorter<Context = <Self as Sorter>::Context>,
+    {
+        SorterThen::new(self, other)
+    }
+}
+
+pub struct SorterThen<T1, T2, Ctx>
+where
+    T1: Sorter<Context = Ctx>,
+    T2: Sorter<Context = Ctx>,
+{
+    sorter1: T1,
+    sorter2: T2,
+}
+
+impl<T1, T2, Ctx> SorterThen<T1, T2, Ctx>
+where
+    T1: Sorter<Context = Ctx>,
+    T2: Sorter<Context = Ctx>,
+{
+    pub fn new(sorter1: T1, sorter2: T2) -> Self {
+        Self { sorter1, sorter2 }
+    }
+}
+
+impl<T1, T2, Ctx> Sorter for SorterThen<T1, T2, Ctx>
+where
+    T1: Sorter<Context = Ctx>,
+    T2: Sorter<Context = Ctx>,
+{
+    type Context = Ctx;
+
+    fn compare(&self, lhs: &Self::Context, rhs: &Self::Context, input: &str) -> std::cmp::Ordering {
+        self.sorter1
+            .compare(lhs, rhs, input)
+            .then_with(|| self.sorter2.compare(lhs, rhs, input))
+    }
+}
+
+/// How [`SorterIf`] compares a pair where only one of the items satisfies the condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixedPair {
+    /// The satisfying item is greater (ranked first, like a higher score)
+    First,
+    /// The satisfying item is less (ranked last)
+    Last,
+    /// Not a total order: an item that doesn't satisfy the condition is equal to every item, while
+    /// two satisfying items may not be. Only for sorters whose result doesn't need to be consistent
+    Equal,
 }
 
+/// Applies the inner sorter only to the pairs whose items both satisfy the condition.
+///
+/// The pairs where neither satisfies it are equal, and the mixed pairs follow
+/// [`SorterIf::mixed`], so that swapping `lhs` and `rhs` always reverses the ordering.
 pub struct SorterIf<T, Cushion, F>
 where
     T: Sorter<Context = Cushion>,
@@ -34,6 +98,7 @@ where
     sorter: T,
 
     f: F,
+    mixed: MixedPair,
 
     _ctx: PhantomData<Cushion>,
 }
@@ -51,11 +116,25 @@ where
         Self {
             sorter: SorterWrapper::new(sorter, transformer),
             f,
+            mixed: MixedPair::First,
             _ctx: PhantomData,
         }
     }
 }
 
+impl<T, Cushion, F> SorterIf<T, Cushion, F>
+where
+    T: Sorter<Context = Cushion>,
+    F: Fn(&Cushion) -> bool + Send,
+    Cushion: Sync,
+{
+    /// The default value is [`MixedPair::First`].
+    pub fn mixed(mut self, mixed: MixedPair) -> Self {
+        self.mixed = mixed;
+        self
+    }
+}
+
 impl<T, Ctx, F> Sorter for SorterIf<T, Ctx, F>
 where
     T: Sorter<Context = Ctx>,
@@ -65,10 +144,19 @@ where
     type Context = Ctx;
 
     fn compare(&self, lhs: &Self::Context, rhs: &Self::Context, input: &str) -> std::cmp::Ordering {
-        if (self.f)(lhs) {
-            self.sorter.compare(lhs, rhs, input)
-        } else {
-            std::cmp::Ordering::Equal
+        use std::cmp::Ordering;
+
+        // 片方だけで判定するとlhsとrhsを入れ替えたときに矛盾してsortが壊れる
+        match ((self.f)(lhs), (self.f)(rhs)) {
+            (true, true) => self.sorter.compare(lhs, rhs, input),
+            (false, false) => Ordering::Equal,
+            (lhs_ok, _) => match self.mixed {
+                MixedPair::First if lhs_ok => Ordering::Greater,
+                MixedPair::First => Ordering::Less,
+                MixedPair::Last if lhs_ok => Ordering::Less,
+                MixedPair::Last => Ordering::Greater,
+                MixedPair::Equal => Ordering::Equal,
+            },
         }
     }
 }
@@ -107,3 +195,100 @@ where
         self.sorter.compare(lhs, rhs, input).reverse()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ltrait::sorter::ClosureSorter;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_then_by() {
+        let by_len =
+            ClosureSorter::new(|lhs: &&str, rhs: &&str, _: &str| lhs.len().cmp(&rhs.len()));
+        let sorter = by_len.then_by(ClosureSorter::new(|lhs: &&str, rhs: &&str, _: &str| {
+            lhs.cmp(rhs)
+        }));
+
+        assert_eq!(sorter.compare(&"b", &"aa", ""), Ordering::Less);
+        assert_eq!(sorter.compare(&"b", &"a", ""), Ordering::Greater);
+        assert_eq!(sorter.compare(&"a", &"a", ""), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_if() {
+        let items = ["bb", "a", "ccc", "dd", "e"];
+        // the condition only holds for some items
+        let sorter = |mixed| {
+            SorterIf::new(
+                ClosureSorter::new(|lhs: &&str, rhs: &&str, _: &str| lhs.cmp(rhs)),
+                |s: &&str| s.len() >= 2,
+                |s: &&str| *s,
+            )
+            .mixed(mixed)
+        };
+
+        for mixed in [MixedPair::First, MixedPair::Last, MixedPair::Equal] {
+            let sorter = sorter(mixed);
+            for lhs in &items {
+                for rhs in &items {
+                    assert_eq!(
+                        sorter.compare(lhs, rhs, ""),
+                        sorter.compare(rhs, lhs, "").reverse(),
+                        "{mixed:?} {lhs} {rhs}"
+                    );
+                }
+            }
+        }
+
+        // Equalは推移的でない(a = bb, a = ccc だが bb < ccc)
+        for mixed in [MixedPair::First, MixedPair::Last] {
+            let sorter = sorter(mixed);
+            for a in &items {
+                for b in &items {
+                    for c in &items {
+                        let ab = sorter.compare(a, b, "");
+                        let bc = sorter.compare(b, c, "");
+                        if ab == bc || bc == Ordering::Equal {
+                            assert_eq!(sorter.compare(a, c, ""), ab, "{mixed:?} {a} {b} {c}");
+                        } else if ab == Ordering::Equal {
+                            assert_eq!(sorter.compare(a, c, ""), bc, "{mixed:?} {a} {b} {c}");
+                        }
+                    }
+                }
+            }
+        }
+
+        assert_eq!(
+            sorter(MixedPair::Equal).compare(&"a", &"bb", ""),
+            Ordering::Equal
+        );
+        assert_eq!(
+            sorter(MixedPair::Equal).compare(&"a", &"e", ""),
+            Ordering::Equal
+        );
+        assert_eq!(
+            sorter(MixedPair::First).compare(&"bb", &"a", ""),
+            Ordering::Greater
+        );
+        assert_eq!(
+            sorter(MixedPair::Last).compare(&"bb", &"a", ""),
+            Ordering::Less
+        );
+        assert_eq!(
+            sorter(MixedPair::Last).compare(&"bb", &"dd", ""),
+            Ordering::Less
+        );
+
+        let mut sorted = items;
+        let first = sorter(MixedPair::First);
+        sorted.sort_by(|lhs, rhs| first.compare(lhs, rhs, ""));
+        assert_eq!(sorted, ["a", "e", "bb", "ccc", "dd"]);
+
+        let last = ClosureSorter::new(|lhs: &&str, rhs: &&str, _: &str| lhs.cmp(rhs))
+            .to_if(|s: &&str| s.len() >= 2, |s: &&str| *s)
+            .mixed(MixedPair::Last);
+        sorted.sort_by(|lhs, rhs| last.compare(lhs, rhs, ""));
+        assert_eq!(sorted, ["bb", "ccc", "dd", "a", "e"]);
+    }
+}
@@ -77,8 +77,11 @@ impl CalcConfig {
     }
 }
 
+type OnError = Box<dyn Fn(String) + Send + Sync>;
+
 pub struct Calc {
     config: CalcConfig,
+    on_error: Option<OnError>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -120,9 +123,75 @@ fn parse(
     }
 }
 
+/// Whether `expr` looks finished, so that its error is worth reporting.
+fn is_complete(expr: &str) -> bool {
+    let expr = expr.trim_end();
+
+    // 閉じすぎている場合は入力途中ではないので報告する
+    let depth = expr.chars().fold(0i32, |depth, c| match c {
+        '(' | '[' | '{' => depth + 1,
+        ')' | ']' | '}' => depth - 1,
+        _ => depth,
+    });
+
+    !expr.is_empty()
+        && depth <= 0
+        && !expr.ends_with([
+            '+', '-', '*', '/', '^', '%', '=', '<', '>', ',', '(', '[', '{',
+        ])
+}
+
 impl Calc {
     pub fn new(config: CalcConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            on_error: None,
+        }
+    }
+
+    /// Called with the message when a formula fails to evaluate (e.g. a syntax error).
+    /// The generator still returns no items, but the error can be shown to the user,
+    /// for example with `ltrait_ui_tui::Notifier::warn`.
+    ///
+    /// Formulas that are still being typed (ending with an operator or with an unclosed
+    /// bracket, e.g. `= 1 +`) are not reported.
+    pub fn on_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(String) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Box::new(f));
+        self
+    }
+
+    fn evaluate(&self, input: &str) -> Vec<String> {
+        match parse(input, self.config.prefix) {
+            Some((engine_type, expr)) => {
+                let result = match engine_type {
+                    Type::Kalk => self.kalk(expr),
+                    Type::Numbat => self.numbat(expr),
+                };
+                self.report(expr, result)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn report(
+        &self,
+        expr: &str,
+        result: Result<String, Box<dyn std::error::Error>>,
+    ) -> Vec<String> {
+        match result {
+            Ok(res) => vec![res],
+            Err(e) => {
+                if let Some(ref on_error) = self.on_error
+                    && is_complete(expr)
+                {
+                    on_error(e.to_string());
+                }
+                Vec::new()
+            }
+        }
     }
 
     fn numbat(&self, input: &str) -> Result<String, Box<dyn std::error::Error>> {
@@ -178,13 +247,7 @@ impl Generator for Calc {
     type Item = String;
 
     async fn generate(&self, input: &str) -> Vec<Self::Item> {
-        match parse(input, self.config.prefix) {
-            Some((engine_type, expr)) => match engine_type {
-                Type::Kalk => self.kalk(expr).map(|res| vec![res]).unwrap_or_default(),
-                Type::Numbat => self.numbat(expr).map(|res| vec![res]).unwrap_or_default(),
-            },
-            _ => Vec::new(),
-        }
+        self.evaluate(input)
     }
 }
 
@@ -192,7 +255,8 @@ impl Generator for Calc {
 mod tests {
     use crate::Calc;
 
-    use super::{parse, Type};
+    use super::{is_complete, parse, Type};
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_parse() -> Result<(), Box<dyn std::error::Error>> {
@@ -232,4 +296,41 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_on_error() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let calc = Calc::new(crate::CalcConfig {
+            prefix: (Some('k'), None),
+            kalk_init_path: None,
+            kalk_precision: None,
+            numbat_init_path: None,
+        })
+        .on_error({
+            let errors = errors.clone();
+            move |e| errors.lock().unwrap().push(e)
+        });
+
+        assert_eq!(calc.evaluate("= 1 + 1"), ["= 2"]);
+        assert!(errors.lock().unwrap().is_empty());
+
+        // still being typed
+        assert!(calc.evaluate("= 1 +").is_empty());
+        assert!(calc.evaluate("= (1 + 2").is_empty());
+        assert!(errors.lock().unwrap().is_empty());
+
+        assert!(calc.evaluate("= 1 + undefined_variable").is_empty());
+        assert_eq!(errors.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_is_complete() {
+        assert!(is_complete("1 + 1"));
+        assert!(is_complete("(1 + 2))"));
+        assert!(is_complete("3!"));
+        assert!(!is_complete("1 + "));
+        assert!(!is_complete("2 m ->"));
+        assert!(!is_complete("sqrt(2"));
+        assert!(!is_complete(""));
+    }
 }
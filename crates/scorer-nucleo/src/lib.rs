@@ -5,12 +5,12 @@
 //! #
 //! # struct DummyUI;
 //! #
-//! # impl<'a> ltrait::UI<'a> for DummyUI {
+//! # impl<Cushion: Send + Sync + 'static> ltrait::UI<Cushion> for DummyUI {
 //! #     type Context = ();
 //! #
-//! #     async fn run<Cushion: 'a + Send>(
+//! #     async fn run(
 //! #         &self,
-//! #         _: ltrait::launcher::batcher::Batcher<'a, Cushion, Self::Context>,
+//! #         _: ltrait::launcher::batcher::Batcher<Cushion, Self::Context>,
 //! #     ) -> Result<Option<Cushion>> {
 //! #         unimplemented!()
 //! #     }
@@ -29,6 +29,8 @@
 //!             CaseMatching::Smart,
 //!             Normalization::Smart,
 //!         )
+//!         // score each entry once per input
+//!         .cached()
 //!         .into_sorter()
 //!     );
 //! #
@@ -41,12 +43,16 @@ use ltrait_extra::scorer::Scorer;
 pub use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
 use nucleo_matcher::{Config, Matcher, Utf32Str};
 
+/// `Hash` and `Eq` so that the scores can be cached with `ltrait_extra::scorer::ScorerExt::cached`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Context {
     pub match_string: String,
 }
 
 pub struct NucleoMatcher {
     matcher: Arc<Mutex<Matcher>>,
+    // 比較のたびにparseし直さないように、最後のinputとそのPatternを持っておく
+    pattern: Mutex<Option<(String, Pattern)>>,
 
     case: CaseMatching,
     normalization: Normalization,
@@ -56,7 +62,17 @@ impl Scorer for NucleoMatcher {
     type Context = Context;
 
     fn predicate_score(&self, ctx: &Self::Context, input: &str) -> u32 {
-        let pat = Pattern::parse(input, self.case, self.normalization);
+        let mut pattern = self.pattern.lock().unwrap();
+
+        if pattern.as_ref().is_none_or(|(last, _)| last != input) {
+            *pattern = Some((
+                input.into(),
+                Pattern::parse(input, self.case, self.normalization),
+            ));
+        }
+
+        let (_, pat) = pattern.as_ref().unwrap();
+
         pat.score(
             Utf32Str::new(&ctx.match_string, &mut Vec::new()),
             &mut self.matcher.lock().unwrap(),
@@ -77,6 +93,7 @@ impl NucleoMatcher {
             case,
             normalization,
             matcher: Arc::new(Mutex::new(Matcher::new(config))),
+            pattern: Mutex::new(None),
         }
     }
 }
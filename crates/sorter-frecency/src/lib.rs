@@ -11,12 +11,12 @@
 //! #
 //! # struct DummyUI;
 //! #
-//! # impl<'a> ltrait::UI<'a> for DummyUI {
+//! # impl<Cushion: Send + Sync + 'static> ltrait::UI<Cushion> for DummyUI {
 //! #     type Context = ();
 //! #
-//! #     async fn run<Cushion: 'a + Send>(
+//! #     async fn run(
 //! #         &self,
-//! #         _: ltrait::launcher::batcher::Batcher<'a, Cushion, Self::Context>,
+//! #         _: ltrait::launcher::batcher::Batcher<Cushion, Self::Context>,
 //! #     ) -> Result<Option<Cushion>> {
 //! #         unimplemented!()
 //! #     }
@@ -50,6 +50,7 @@ use std::time::Duration;
 use chrono::{DateTime, Utc};
 use ltrait::color_eyre::eyre::{OptionExt, Result, WrapErr};
 use ltrait::{Action, Sorter};
+use ltrait_extra::scorer::Scorer;
 use rusqlite::{Connection, params};
 
 /// The context of ltrait-sorter-frecency
@@ -149,15 +150,25 @@ impl Sorter for Frecency {
     type Context = Context;
 
     fn compare(&self, lhs: &Self::Context, rhs: &Self::Context, _: &str) -> std::cmp::Ordering {
-        ((self.entries.get(&lhs.ident))
-            .map(|e| e.score)
-            .unwrap_or_default())
-        .partial_cmp(
-            &(self.entries.get(&rhs.ident))
-                .map(|e| e.score)
-                .unwrap_or_default(),
-        )
-        .unwrap_or(std::cmp::Ordering::Equal)
+        self.score(&lhs.ident)
+            .partial_cmp(&self.score(&rhs.ident))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }
+}
+
+impl Frecency {
+    fn score(&self, ident: &str) -> f64 {
+        self.entries.get(ident).map(|e| e.score).unwrap_or_default()
+    }
+}
+
+/// The frecency score ×100 (rounded) so that its fraction isn't lost, e.g. to blend it with other
+/// scores with `ltrait_extra::scorer::WeightedSorter`.
+impl Scorer for Frecency {
+    type Context = Context;
+
+    fn predicate_score(&self, ctx: &Self::Context, _: &str) -> u32 {
+        (self.score(&ctx.ident) * 100.).round() as u32
     }
 }
 
@@ -5,12 +5,12 @@
 //! #
 //! # struct DummyUI;
 //! #
-//! # impl<'a> ltrait::UI<'a> for DummyUI {
+//! # impl<Cushion: Send + Sync + 'static> ltrait::UI<Cushion> for DummyUI {
 //! #     type Context = ();
 //! #
-//! #     async fn run<Cushion: 'a + Send>(
+//! #     async fn run(
 //! #         &self,
-//! #         _: ltrait::launcher::batcher::Batcher<'a, Cushion, Self::Context>,
+//! #         _: ltrait::launcher::batcher::Batcher<Cushion, Self::Context>,
 //! #     ) -> Result<Option<Cushion>> {
 //! #         unimplemented!()
 //! #     }
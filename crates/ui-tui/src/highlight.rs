@@ -0,0 +1,163 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+use std::ops::Range;
+
+/// Styles spans of the input line. Set it with [`crate::TuiConfig::highlighter`].
+///
+/// Ranges are byte offsets into `input`. Ranges that overlap an earlier one or that are not on
+/// char boundaries are ignored.
+pub trait Highlighter: Send + Sync {
+    fn highlight(&self, input: &str) -> Vec<(Range<usize>, Style)>;
+}
+
+impl<F> Highlighter for F
+where
+    F: Fn(&str) -> Vec<(Range<usize>, Style)> + Send + Sync,
+{
+    fn highlight(&self, input: &str) -> Vec<(Range<usize>, Style)> {
+        self(input)
+    }
+}
+
+/// Highlights the mode prefix of the input, e.g. `=` and `=k ` of ltrait-gen-calc.
+///
+/// The longest matching prefix wins. If an error style is set, a leading word that starts like
+/// a prefix but can no longer become one (e.g. `=x` when the modes are `= ` and `=k `) is
+/// styled with it.
+///
+/// ```
+/// use ltrait_ui_tui::{PrefixHighlighter, style::{Style, Stylize as _}};
+///
+/// let highlighter = PrefixHighlighter::default()
+///     .mode("= ", Style::new().yellow())
+///     .mode("=k ", Style::new().magenta())
+///     .error_style(Style::new().red().underlined());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PrefixHighlighter {
+    modes: Vec<(String, Style)>,
+    error: Option<Style>,
+}
+
+impl PrefixHighlighter {
+    pub fn mode(mut self, prefix: impl Into<String>, style: Style) -> Self {
+        self.modes.push((prefix.into(), style));
+        self
+    }
+
+    pub fn error_style(mut self, style: Style) -> Self {
+        self.error = Some(style);
+        self
+    }
+}
+
+impl Highlighter for PrefixHighlighter {
+    fn highlight(&self, input: &str) -> Vec<(Range<usize>, Style)> {
+        if let Some((prefix, style)) = self
+            .modes
+            .iter()
+            .filter(|(prefix, _)| input.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+        {
+            return vec![(0..prefix.len(), *style)];
+        }
+
+        let Some(error) = self.error else {
+            return vec![];
+        };
+
+        let word = &input[..input.find(char::is_whitespace).unwrap_or(input.len())];
+        let looks_like_prefix = self
+            .modes
+            .iter()
+            .any(|(prefix, _)| input.chars().next().is_some_and(|c| prefix.starts_with(c)));
+        // まだ入力途中ならerrorにしない
+        let can_complete = self
+            .modes
+            .iter()
+            .any(|(prefix, _)| prefix.starts_with(input));
+
+        if looks_like_prefix && !can_complete {
+            vec![(0..word.len().max(1).min(input.len()), error)]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Split `input` into spans styled with `highlights`.
+pub(crate) fn styled_line(input: &str, mut highlights: Vec<(Range<usize>, Style)>) -> Line<'_> {
+    highlights.sort_by_key(|(range, _)| range.start);
+
+    let mut spans = vec![];
+    let mut last = 0;
+
+    for (range, style) in highlights {
+        if range.start < last
+            || range.end > input.len()
+            || range.is_empty()
+            || !input.is_char_boundary(range.start)
+            || !input.is_char_boundary(range.end)
+        {
+            continue;
+        }
+
+        if last < range.start {
+            spans.push(Span::raw(&input[last..range.start]));
+        }
+        spans.push(Span::styled(&input[range.clone()], style));
+        last = range.end;
+    }
+
+    if last < input.len() {
+        spans.push(Span::raw(&input[last..]));
+    }
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Stylize as _;
+
+    #[test]
+    fn test_prefix_highlighter() {
+        let numbat = Style::new().yellow();
+        let kalk = Style::new().magenta();
+        let error = Style::new().red();
+
+        let highlighter = PrefixHighlighter::default()
+            .mode("= ", numbat)
+            .mode("=k ", kalk)
+            .error_style(error);
+
+        assert_eq!(highlighter.highlight("= 1 + 1"), vec![(0..2, numbat)]);
+        assert_eq!(highlighter.highlight("=k 1 + 1"), vec![(0..3, kalk)]);
+        assert_eq!(highlighter.highlight("=k"), vec![]);
+        assert_eq!(highlighter.highlight("=x 1 + 1"), vec![(0..2, error)]);
+        assert_eq!(highlighter.highlight("firefox"), vec![]);
+        assert_eq!(highlighter.highlight(""), vec![]);
+    }
+
+    #[test]
+    fn test_styled_line() {
+        let red = Style::new().red();
+
+        assert_eq!(
+            styled_line("=k 1", vec![(0..3, red)]),
+            Line::from(vec![Span::styled("=k ", red), Span::raw("1")])
+        );
+        // overlapping and out of range highlights are ignored
+        assert_eq!(
+            styled_line(
+                "abc",
+                vec![(1..2, red), (0..2, red), (2..10, red), (3..3, red)]
+            ),
+            Line::from(vec![Span::styled("ab", red), Span::raw("c")])
+        );
+    }
+}
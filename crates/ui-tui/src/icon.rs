@@ -0,0 +1,377 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "icon")]
+use std::collections::HashMap;
+
+/// How icons of [`crate::TuiEntry`] are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconProtocol {
+    /// Guess the protocol from the environment variables, falling back to [`IconProtocol::Glyph`]
+    Auto,
+    /// [kitty graphics protocol](https://sw.kovidgoyal.net/kitty/graphics-protocol/)
+    Kitty,
+    Sixel,
+    /// Draw the glyph set by [`crate::TuiConfig::icon_glyph`] instead of the image
+    Glyph,
+}
+
+/// The width of an icon in cells. The height is always one cell row.
+pub(crate) const ICON_WIDTH: u16 = 2;
+
+impl IconProtocol {
+    /// Resolve [`IconProtocol::Auto`] and the protocols unavailable in this build.
+    pub(crate) fn resolve(self) -> Self {
+        match self {
+            _ if !cfg!(feature = "icon") => Self::Glyph,
+            Self::Auto => Self::detect(),
+            protocol => protocol,
+        }
+    }
+
+    // DA1などで問い合わせるとEventStreamと取り合いになるので環境変数だけで判断する
+    fn detect() -> Self {
+        let var = |key| std::env::var(key).unwrap_or_default();
+
+        let term = var("TERM");
+        let term_program = var("TERM_PROGRAM");
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || matches!(term_program.as_str(), "WezTerm" | "ghostty")
+        {
+            Self::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || matches!(term_program.as_str(), "iTerm.app" | "contour")
+        {
+            Self::Sixel
+        } else {
+            Self::Glyph
+        }
+    }
+}
+
+#[cfg(feature = "icon")]
+enum Cached {
+    /// The image id on the terminal side
+    Kitty(u32),
+    Sixel(String),
+    /// Failed to load or encode. The glyph is drawn instead
+    Failed,
+}
+
+/// Draws icons with a graphics protocol after ratatui has drawn the frame.
+///
+/// Images are loaded once per path and kept in the cache for the whole session.
+pub(crate) struct IconRenderer {
+    #[cfg_attr(not(feature = "icon"), allow(dead_code))]
+    protocol: IconProtocol,
+    #[cfg(feature = "icon")]
+    cache: HashMap<PathBuf, Cached>,
+    #[cfg(feature = "icon")]
+    next_id: u32,
+    /// Escape sequences transmitting newly loaded images to the terminal
+    #[cfg(feature = "icon")]
+    pending: String,
+    /// Positions where an image was drawn on the previous frame
+    previous: Vec<(u16, u16)>,
+}
+
+impl IconRenderer {
+    pub(crate) fn new(protocol: IconProtocol) -> Self {
+        Self {
+            protocol,
+            #[cfg(feature = "icon")]
+            cache: HashMap::new(),
+            #[cfg(feature = "icon")]
+            next_id: 1,
+            #[cfg(feature = "icon")]
+            pending: String::new(),
+            previous: vec![],
+        }
+    }
+
+    /// Whether the image of `path` can't be drawn and the glyph should be used.
+    pub(crate) fn is_failed(&self, path: &Path) -> bool {
+        #[cfg(feature = "icon")]
+        {
+            matches!(self.cache.get(path), Some(Cached::Failed))
+        }
+        #[cfg(not(feature = "icon"))]
+        {
+            let _ = path;
+            true
+        }
+    }
+
+    /// Draw `placements` (`(x, y, path)` in cells) on `writer`.
+    ///
+    /// Returns true if an image failed to load for the first time, in which case the frame
+    /// should be drawn again so that the glyph is shown instead.
+    pub(crate) fn draw<W: Write>(
+        &mut self,
+        writer: &mut W,
+        placements: &[(u16, u16, PathBuf)],
+    ) -> std::io::Result<bool> {
+        use crossterm::{cursor, queue, style::Print};
+
+        #[allow(unused_mut)]
+        let mut newly_failed = false;
+
+        #[cfg(feature = "icon")]
+        {
+            for (_, _, path) in placements {
+                newly_failed |= !self.cache.contains_key(path.as_path())
+                    && matches!(self.load(path), Cached::Failed);
+            }
+
+            // 画像の転送は初めてその画像を使う時だけ
+            let pending = std::mem::take(&mut self.pending);
+            writer.write_all(pending.as_bytes())?;
+
+            if self.protocol == IconProtocol::Kitty {
+                // 前のframeのplacementを全部消す(画像のデータは残る)
+                write!(writer, "\x1b_Ga=d,d=a,q=2\x1b\\")?;
+            }
+        }
+
+        // sixelは上書きされない限り残り続けるので、前のframeで使っていた場所は空白で塗りつぶす
+        for (x, y) in std::mem::take(&mut self.previous) {
+            if !placements.iter().any(|(px, py, _)| (*px, *py) == (x, y)) {
+                queue!(
+                    writer,
+                    cursor::MoveTo(x, y),
+                    Print(" ".repeat(ICON_WIDTH as usize))
+                )?;
+            }
+        }
+
+        #[cfg(feature = "icon")]
+        for (x, y, path) in placements {
+            match &self.cache[path.as_path()] {
+                Cached::Kitty(id) => {
+                    queue!(writer, cursor::MoveTo(*x, *y))?;
+                    write!(writer, "\x1b_Ga=p,i={id},c={ICON_WIDTH},r=1,C=1,q=2\x1b\\")?;
+                    self.previous.push((*x, *y));
+                }
+                Cached::Sixel(sixel) => {
+                    // 透過した部分に前の画像が残らないように一回文字で上書きしておく
+                    queue!(
+                        writer,
+                        cursor::MoveTo(*x, *y),
+                        Print(" ".repeat(ICON_WIDTH as usize)),
+                        cursor::MoveTo(*x, *y),
+                        Print(sixel)
+                    )?;
+                    self.previous.push((*x, *y));
+                }
+                Cached::Failed => {}
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(newly_failed)
+    }
+}
+
+#[cfg(feature = "icon")]
+impl IconRenderer {
+    fn load(&mut self, path: &Path) -> &Cached {
+        if !self.cache.contains_key(path) {
+            let cached = self.encode(path).unwrap_or(Cached::Failed);
+            self.cache.insert(path.to_path_buf(), cached);
+        }
+
+        &self.cache[path]
+    }
+
+    fn encode(&mut self, path: &Path) -> Option<Cached> {
+        let img = image::open(path).ok()?;
+
+        match self.protocol {
+            IconProtocol::Kitty => {
+                use base64::{Engine as _, engine::general_purpose};
+
+                let mut png = std::io::Cursor::new(vec![]);
+                img.write_to(&mut png, image::ImageFormat::Png).ok()?;
+
+                let id = self.next_id;
+                self.next_id += 1;
+
+                self.pending.push_str(&kitty_transmit(
+                    id,
+                    &general_purpose::STANDARD.encode(png.get_ref()),
+                ));
+
+                Some(Cached::Kitty(id))
+            }
+            IconProtocol::Sixel => {
+                let cell_height = crossterm::terminal::window_size()
+                    .ok()
+                    .filter(|size| size.rows != 0 && size.height != 0)
+                    .map(|size| (size.height / size.rows) as u32)
+                    .unwrap_or(16);
+
+                let img = img
+                    .resize_exact(
+                        cell_height,
+                        cell_height,
+                        image::imageops::FilterType::Triangle,
+                    )
+                    .to_rgba8();
+
+                Some(Cached::Sixel(sixel(&img)))
+            }
+            IconProtocol::Auto | IconProtocol::Glyph => None,
+        }
+    }
+}
+
+/// Transmit a PNG (already base64 encoded) with the id without displaying it.
+#[cfg(feature = "icon")]
+fn kitty_transmit(id: u32, png_base64: &str) -> String {
+    // escape codeのpayloadは4096byteごとに分割する必要がある
+    let chunks: Vec<_> = png_base64.as_bytes().chunks(4096).collect();
+    let mut out = String::new();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 != chunks.len()) as u8;
+        let chunk = std::str::from_utf8(chunk).unwrap();
+
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=t,f=100,i={id},q=2,m={more};{chunk}\x1b\\"
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+
+    out
+}
+
+/// Encode the image as sixel with a 6x6x6 color cube. Transparent pixels are not drawn.
+#[cfg(feature = "icon")]
+fn sixel(img: &image::RgbaImage) -> String {
+    use std::fmt::Write as _;
+
+    let (width, height) = img.dimensions();
+    let level = |c: u8| (c as u16 * 5 / 255) as usize;
+    let color = |x: u32, y: u32| {
+        let [r, g, b, a] = img.get_pixel(x, y).0;
+        (a >= 128).then(|| level(r) * 36 + level(g) * 6 + level(b))
+    };
+
+    // P2=1で背景を透過させる
+    let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+
+    for i in 0..216 {
+        let percent = |l: usize| l * 100 / 5;
+        let _ = write!(
+            out,
+            "#{i};2;{};{};{}",
+            percent(i / 36),
+            percent(i / 6 % 6),
+            percent(i % 6)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        let mut used: Vec<usize> = (band..(band + 6).min(height))
+            .flat_map(|y| (0..width).filter_map(move |x| color(x, y)))
+            .collect();
+        used.sort_unstable();
+        used.dedup();
+
+        for c in used {
+            let _ = write!(out, "#{c}");
+
+            let columns: Vec<u8> = (0..width)
+                .map(|x| {
+                    (0..6)
+                        .filter(|dy| band + dy < height && color(x, band + dy) == Some(c))
+                        .fold(0u8, |bits, dy| bits | 1 << dy)
+                })
+                .collect();
+
+            // run-length encoding
+            let mut x = 0;
+            while x < columns.len() {
+                let run = columns[x..]
+                    .iter()
+                    .take_while(|b| **b == columns[x])
+                    .count();
+                let ch = (63 + columns[x]) as char;
+
+                if run > 3 {
+                    let _ = write!(out, "!{run}{ch}");
+                } else {
+                    out.extend(std::iter::repeat_n(ch, run));
+                }
+                x += run;
+            }
+
+            out.push('$');
+        }
+
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+#[cfg(all(test, feature = "icon"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kitty_transmit() {
+        assert_eq!(
+            kitty_transmit(1, "AAAA"),
+            "\x1b_Ga=t,f=100,i=1,q=2,m=0;AAAA\x1b\\"
+        );
+
+        let payload = "A".repeat(4096 * 2 + 10);
+        let out = kitty_transmit(7, &payload);
+        let chunks: Vec<_> = out.split_terminator("\x1b\\").collect();
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks[0],
+            format!("\x1b_Ga=t,f=100,i=7,q=2,m=1;{}", "A".repeat(4096))
+        );
+        assert_eq!(chunks[1], format!("\x1b_Gm=1;{}", "A".repeat(4096)));
+        assert_eq!(chunks[2], format!("\x1b_Gm=0;{}", "A".repeat(10)));
+    }
+
+    #[test]
+    fn test_sixel() {
+        use image::{Rgba, RgbaImage};
+
+        let registers = |out: &str| out.matches(";2;").count();
+
+        // 1行だけの赤い画像はrun-lengthで1つにまとまる
+        let red = RgbaImage::from_pixel(5, 1, Rgba([255, 0, 0, 255]));
+        let out = sixel(&red);
+        assert!(out.starts_with("\x1bP0;1;0q\"1;1;5;1#0;2;0;0;0#1;2;0;0;20"));
+        assert!(out.contains("#180;2;100;0;0#181;2;100;0;20"));
+        assert_eq!(registers(&out), 216);
+        assert!(out.ends_with("#215;2;100;100;100#180!5@$-\x1b\\"));
+
+        // 上の6行が青、7行目は緑と透明
+        let mut img = RgbaImage::from_pixel(3, 7, Rgba([0, 0, 255, 255]));
+        img.put_pixel(0, 6, Rgba([0, 255, 0, 255]));
+        img.put_pixel(1, 6, Rgba([0, 0, 0, 0]));
+        img.put_pixel(2, 6, Rgba([0, 255, 0, 255]));
+        let out = sixel(&img);
+        assert!(out.starts_with("\x1bP0;1;0q\"1;1;3;7"));
+        assert!(out.ends_with("#215;2;100;100;100#5~~~$-#30@?@$-\x1b\\"));
+    }
+}
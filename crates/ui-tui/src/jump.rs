@@ -0,0 +1,40 @@
+/// Generate `n` labels from `alphabet` (easymotion-style).
+///
+/// If `alphabet` has enough characters every label is a single letter, otherwise every
+/// label has two letters so that no label is a prefix of another one.
+/// Labels that can't be represented even with two letters are omitted.
+pub(crate) fn labels(alphabet: &[char], n: usize) -> Vec<String> {
+    if n <= alphabet.len() {
+        alphabet.iter().take(n).map(|c| c.to_string()).collect()
+    } else {
+        alphabet
+            .iter()
+            .flat_map(|first| {
+                alphabet
+                    .iter()
+                    .map(move |second| format!("{first}{second}"))
+            })
+            .take(n)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::labels;
+
+    #[test]
+    fn test_labels() {
+        let alphabet = ['a', 's', 'd'];
+
+        assert_eq!(labels(&alphabet, 0), Vec::<String>::new());
+        assert_eq!(labels(&alphabet, 2), vec!["a", "s"]);
+        assert_eq!(labels(&alphabet, 3), vec!["a", "s", "d"]);
+        assert_eq!(
+            labels(&alphabet, 4),
+            vec!["aa", "as", "ad", "sa"],
+            "no label may be a prefix of another one"
+        );
+        assert_eq!(labels(&alphabet, 100).len(), 9);
+    }
+}
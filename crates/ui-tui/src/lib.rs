@@ -6,7 +6,11 @@ use ltrait::{
 };
 
 use crossterm::{
-    event::{Event as CEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    cursor::MoveTo,
+    event::{
+        Event as CEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode},
 };
@@ -14,8 +18,9 @@ use ratatui::{
     Frame, Terminal, TerminalOptions,
     layout::{Constraint, Direction, Layout},
     prelude::{Backend, CrosstermBackend},
-    style::Style,
-    widgets::{Block, Borders, Clear, List, Paragraph, Widget},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, Paragraph, Tabs, Widget},
 };
 use tracing::{debug, info};
 use tui_input::{Input, backend::crossterm::EventHandler};
@@ -25,13 +30,126 @@ pub use ratatui::{Viewport, style};
 use futures::{FutureExt as _, select};
 use tokio::sync::mpsc;
 
-use std::{io::Write, sync::RwLock};
+use std::{
+    io::Write,
+    ops::Range,
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+mod highlight;
+mod icon;
+mod jump;
+mod notify;
+#[cfg(feature = "record")]
+pub mod record;
+
+pub use highlight::{Highlighter, PrefixHighlighter};
+pub use icon::IconProtocol;
+use icon::{ICON_WIDTH, IconRenderer};
+pub use notify::{Level, Notification, Notifier};
 
+/// Clones share the notifier, the chosen action, the last query and the state kept by
+/// [`Action::Reload`] (including the screen, which is restored when the last clone is dropped
+/// without running again).
+#[derive(Clone)]
 pub struct Tui<F>
 where
     F: Fn(&KeyEvent) -> Action + Clone,
 {
     config: TuiConfig<F>,
+    chosen_action: ChosenAction,
+    last_query: LastQuery,
+    notifier: Notifier,
+    resume: Arc<Mutex<Option<Resume>>>,
+    screen: Arc<Mutex<Option<KeptScreen>>>,
+}
+
+type TuiTerminal = Terminal<CrosstermBackend<Box<dyn Write + Send>>>;
+
+/// The terminal kept in the alternate screen by [`Action::Reload`], so that the loading state
+/// stays on the screen while the launcher is built again.
+struct KeptScreen {
+    // (terminal, enhanced)
+    inner: Option<(TuiTerminal, bool)>,
+}
+
+impl KeptScreen {
+    fn take(mut self) -> (TuiTerminal, bool) {
+        self.inner.take().unwrap()
+    }
+}
+
+impl Drop for KeptScreen {
+    fn drop(&mut self) {
+        if let Some((mut terminal, enhanced)) = self.inner.take() {
+            let _ = leave_screen(&mut terminal, enhanced);
+        }
+    }
+}
+
+/// The state saved by [`Action::Reload`] and restored by the next run.
+struct Resume {
+    input: String,
+    mode_i: usize,
+    /// The text of the highlighted entry
+    highlighted: Option<String>,
+}
+
+/// A handle to the id of the [`EntryAction`] chosen in the last run of [`Tui`].
+///
+/// `Launcher` takes the ownership of the UI, so get the handle with [`Tui::chosen_action`]
+/// before and read it in actions.
+///
+/// ```
+/// # use ltrait::action::ClosureAction;
+/// # use ltrait_ui_tui::{Tui, TuiConfig, Viewport, sample_keyconfig};
+/// let tui = Tui::new(TuiConfig::new(Viewport::Fullscreen, false, '>', ' ', sample_keyconfig));
+/// let chosen = tui.chosen_action();
+///
+/// let action = ClosureAction::new(move |_: &()| {
+///     if chosen.get().as_deref() == Some("terminal") {
+///         // launch in a terminal
+///     }
+///     Ok(())
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChosenAction(Arc<Mutex<Option<String>>>);
+
+impl ChosenAction {
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// A handle to the input and the mode of the last run of [`Tui`], e.g. to log them in actions.
+/// Get it with [`Tui::last_query`] like [`ChosenAction`].
+#[derive(Debug, Clone, Default)]
+pub struct LastQuery(Arc<Mutex<Option<InputAndMode>>>);
+
+/// The input and the name of the mode
+type InputAndMode = (String, Option<String>);
+
+impl LastQuery {
+    /// The input without the prefix of the mode.
+    pub fn input(&self) -> Option<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(input, _)| input.clone())
+    }
+
+    /// The name of the [`TuiMode`].
+    pub fn mode(&self) -> Option<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|(_, mode)| mode.clone())
+    }
 }
 
 impl<Cushion, F> UI<Cushion> for Tui<F>
@@ -41,36 +159,126 @@ where
 {
     type Context = TuiEntry;
 
-    async fn run(&self, mut batcher: Batcher<Cushion, Self::Context>) -> Result<Option<Cushion>> {
-        let writer: Box<dyn Write + Send> = if self.config.use_tty {
-            let tty = std::fs::OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open("/dev/tty")?;
-            Box::new(tty)
-        } else {
-            Box::new(std::io::stdout())
+    /// The id of the chosen [`EntryAction`] can be read from [`Tui::chosen_action`].
+    async fn run(&self, batcher: Batcher<Cushion, Self::Context>) -> Result<Option<Cushion>> {
+        let selected = self.run_with_action(batcher).await?;
+
+        Ok(selected.map(|(cushion, action)| {
+            *self.chosen_action.0.lock().unwrap() = action;
+            cushion
+        }))
+    }
+}
+
+impl<F> Tui<F>
+where
+    F: Fn(&KeyEvent) -> Action + Send + Sync + Clone,
+{
+    /// Same as [`UI::run`], but also returns the id of the [`EntryAction`] chosen from the
+    /// action menu. The id is `None` if the entry was selected with [`Action::Select`].
+    pub async fn run_with_action<Cushion: Send>(
+        &self,
+        mut batcher: Batcher<Cushion, TuiEntry>,
+    ) -> Result<Option<(Cushion, Option<String>)>> {
+        *self.chosen_action.0.lock().unwrap() = None;
+
+        let kept = self.screen.lock().unwrap().take().map(KeptScreen::take);
+        let (mut terminal, kept_enhanced) = match kept {
+            Some((terminal, enhanced)) => (terminal, Some(enhanced)),
+            None => (
+                Terminal::with_options(
+                    CrosstermBackend::new(self.writer()?),
+                    TerminalOptions {
+                        viewport: self.config.viewport.clone(),
+                    },
+                )?,
+                None,
+            ),
         };
 
-        let backend = CrosstermBackend::new(writer);
+        let mut app = App::new(self.config.clone(), self.notifier.clone());
 
-        let mut terminal = Terminal::with_options(
-            backend,
-            TerminalOptions {
-                viewport: self.config.viewport.clone(),
-            },
-        )?;
+        let resume = self.resume.lock().unwrap().take();
+        let resumed = resume.is_some();
+        if let Some(resume) = resume {
+            app.input = Input::new(resume.input);
+            app.mode_i = resume.mode_i;
+            app.restore_highlight = resume.highlighted;
+        }
 
-        self.enter(&mut terminal)?;
+        #[cfg(feature = "record")]
+        if let Some(path) = &self.config.record {
+            app.recorder = Some(record::Recorder::create(path)?);
+            app.record(|app| record::RecordEvent::Start {
+                input: app.input.value().into(),
+                mode_i: app.mode_i,
+            });
+        }
 
-        let i = App::new(self.config.clone())
-            .run(&mut terminal, &mut batcher)
-            .await;
+        let query = app.query();
+        if !query.is_empty() {
+            // modeのprefixやreload前の入力を付けた状態から始める
+            batcher.input(&mut app.buffer, &query);
+        }
 
-        self.exit(&mut terminal)?;
+        // reloadで戻ってきた場合は、勝手に選択したり終了したりしない
+        if !resumed && (self.config.select_one || self.config.exit_zero) {
+            let buffer = Self::prefetch(&mut batcher).await?;
 
-        Ok(if let Some(id) = i? {
-            Some(batcher.compute_cushion(id)?)
+            match buffer.len() {
+                0 if self.config.exit_zero => return Err(TuiError::NoMatch.into()),
+                1 if self.config.select_one => {
+                    *self.last_query.0.lock().unwrap() = Some(app.last_query());
+
+                    let (_, id) = buffer.next(&mut Position::default()).unwrap();
+                    return Ok(Some((batcher.compute_cushion(*id)?, None)));
+                }
+                _ => {
+                    app.buffer = buffer;
+                    app.has_more = false;
+
+                    #[cfg(feature = "record")]
+                    app.record_merge();
+                }
+            }
+        }
+
+        let enhanced = match kept_enhanced {
+            Some(enhanced) => enhanced,
+            None => self.enter(&mut terminal)?,
+        };
+
+        let i = app.run(&mut terminal, &mut batcher).await;
+
+        *self.last_query.0.lock().unwrap() = Some(app.last_query());
+
+        if app.reload && i.is_ok() {
+            *self.resume.lock().unwrap() = Some(Resume {
+                input: app.input.value().into(),
+                mode_i: app.mode_i,
+                highlighted: app.highlighted().map(|(entry, _)| entry.text.0.clone()),
+            });
+
+            // 描画に失敗した場合もdropで画面を戻す
+            let mut kept = KeptScreen {
+                inner: Some((terminal, enhanced)),
+            };
+            let (terminal, _) = kept.inner.as_mut().unwrap();
+
+            // launcherを作り直している間もloadingを表示しておく
+            app.has_more = true;
+            terminal.draw(|frame| app.draw(frame))?;
+            app.draw_icons(terminal)?;
+
+            *self.screen.lock().unwrap() = Some(kept);
+
+            return Err(TuiError::Reload.into());
+        }
+
+        leave_screen(&mut terminal, enhanced)?;
+
+        Ok(if let Some((id, action)) = i? {
+            Some((batcher.compute_cushion(id)?, action))
         } else {
             None
         })
@@ -82,33 +290,95 @@ where
     F: Fn(&KeyEvent) -> Action + Clone,
 {
     pub fn new(config: TuiConfig<F>) -> Self {
-        Self { config }
+        Self {
+            config,
+            chosen_action: ChosenAction::default(),
+            last_query: LastQuery::default(),
+            notifier: Notifier::default(),
+            resume: Arc::new(Mutex::new(None)),
+            screen: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn chosen_action(&self) -> ChosenAction {
+        self.chosen_action.clone()
+    }
+
+    pub fn last_query(&self) -> LastQuery {
+        self.last_query.clone()
     }
 
-    fn enter<B: Backend + Write>(&self, terminal: &mut Terminal<B>) -> Result<()> {
+    pub fn notifier(&self) -> Notifier {
+        self.notifier.clone()
+    }
+
+    fn writer(&self) -> Result<Box<dyn Write + Send>> {
+        Ok(if self.config.use_tty {
+            let tty = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/tty")?;
+            Box::new(tty)
+        } else {
+            Box::new(std::io::stdout())
+        })
+    }
+
+    /// Drain every source and generator for the initial input without showing the UI.
+    async fn prefetch<Cushion: Send>(
+        batcher: &mut Batcher<Cushion, TuiEntry>,
+    ) -> Result<Buffer<(TuiEntry, usize)>> {
+        let mut buffer = Buffer::default();
+        let mut more = true;
+
+        while more {
+            let from = batcher.prepare().await;
+            more = batcher.merge(&mut buffer, from)?;
+        }
+
+        Ok(buffer)
+    }
+
+    /// Returns whether the keyboard enhancement flags were pushed.
+    fn enter<B: Backend + Write>(&self, terminal: &mut Terminal<B>) -> Result<bool> {
         execute!(
             terminal.backend_mut(),
             crossterm::terminal::EnterAlternateScreen,
             crossterm::event::EnableMouseCapture
         )?;
         enable_raw_mode()?;
+
+        // 対応していない端末は`CSI > u`を無視するので問い合わせない
+        // (crosstermの問い合わせはstdoutに書かれることがあり、stdoutがpipeだと応答を待って固まる)
+        let enhanced = match self.config.keyboard_enhancement {
+            Some(flags) => {
+                execute!(terminal.backend_mut(), PushKeyboardEnhancementFlags(flags))?;
+                true
+            }
+            None => false,
+        };
+
         terminal.clear()?;
 
-        Ok(())
+        Ok(enhanced)
     }
+}
 
-    fn exit<B: Backend + Write>(&self, terminal: &mut Terminal<B>) -> Result<()> {
-        execute!(
-            terminal.backend_mut(),
-            crossterm::terminal::LeaveAlternateScreen,
-            crossterm::event::DisableMouseCapture
-        )?;
+fn leave_screen<B: Backend + Write>(terminal: &mut Terminal<B>, enhanced: bool) -> Result<()> {
+    if enhanced {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
 
-        disable_raw_mode()?;
-        ratatui::restore();
+    execute!(
+        terminal.backend_mut(),
+        crossterm::terminal::LeaveAlternateScreen,
+        crossterm::event::DisableMouseCapture
+    )?;
 
-        Ok(())
-    }
+    disable_raw_mode()?;
+    ratatui::restore();
+
+    Ok(())
 }
 
 #[derive(Clone)]
@@ -121,12 +391,28 @@ where
     selecting: char,
     no_selecting: char,
     keybinder: F,
+    select_one: bool,
+    exit_zero: bool,
+    jump_labels: Vec<char>,
+    jump_select: bool,
+    icons: Option<IconProtocol>,
+    icon_glyph: char,
+    keyboard_enhancement: Option<KeyboardEnhancementFlags>,
+    highlighter: Option<Arc<dyn Highlighter>>,
+    modes: Vec<TuiMode>,
+    toast_duration: Duration,
+    max_entry_height: usize,
+    #[cfg(feature = "record")]
+    record: Option<PathBuf>,
 }
 
 impl<F> TuiConfig<F>
 where
     F: Fn(&KeyEvent) -> Action + Clone,
 {
+    /// If `use_tty` is true, the UI is drawn on `/dev/tty` instead of stdout, so that stdout can
+    /// be piped. Keys are read from `/dev/tty` when stdin isn't a TTY, so stdin can be piped
+    /// too (`ls | my-launcher`).
     pub fn new(
         viewport: Viewport,
         use_tty: bool,
@@ -140,16 +426,231 @@ where
             selecting,
             no_selecting,
             keybinder,
+            select_one: false,
+            exit_zero: false,
+            jump_labels: "asdfghjklqwertyuiopzxcvbnm".chars().collect(),
+            jump_select: false,
+            icons: None,
+            icon_glyph: '◆',
+            keyboard_enhancement: Some(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES),
+            highlighter: None,
+            modes: vec![],
+            toast_duration: Duration::from_secs(5),
+            max_entry_height: 2,
+            #[cfg(feature = "record")]
+            record: None,
         }
     }
+
+    /// If `select_one` is true and exactly one entry matches the initial input once all
+    /// sources have been drained, that entry is returned without showing the UI
+    /// (like fzf's `--select-1`).
+    /// The default value is false.
+    pub fn select_one(mut self, flag: bool) -> Self {
+        self.select_one = flag;
+        self
+    }
+
+    /// If `exit_zero` is true and no entry matches the initial input once all sources have
+    /// been drained, `run` returns [`TuiError::NoMatch`] without showing the UI
+    /// (like fzf's `--exit-0`).
+    /// The default value is false.
+    pub fn exit_zero(mut self, flag: bool) -> Self {
+        self.exit_zero = flag;
+        self
+    }
+
+    /// The characters used to build the labels shown by [`Action::Jump`].
+    /// Labels are one letter long if there are enough characters for every visible entry,
+    /// two letters long otherwise.
+    /// The default value is `"asdfghjklqwertyuiopzxcvbnm"`.
+    pub fn jump_labels(mut self, labels: &str) -> Self {
+        self.jump_labels = labels.chars().collect();
+        self
+    }
+
+    /// If `jump_select` is true, typing a jump label selects the entry and exits.
+    /// Otherwise it only moves the highlight to the entry.
+    /// The default value is false.
+    pub fn jump_select(mut self, flag: bool) -> Self {
+        self.jump_select = flag;
+        self
+    }
+
+    /// Show [`TuiEntry::icon`] in a column between the selecting status and the text.
+    /// Images are drawn with a graphics protocol only if the `icon` feature is enabled,
+    /// otherwise (or if the image can't be loaded) the glyph set by
+    /// [`TuiConfig::icon_glyph`] is drawn.
+    /// The default value is `None`, which means no icon column.
+    pub fn icons(mut self, protocol: Option<IconProtocol>) -> Self {
+        self.icons = protocol;
+        self
+    }
+
+    /// The glyph drawn in place of an icon that can't be shown as an image.
+    /// The default value is `'◆'`.
+    pub fn icon_glyph(mut self, glyph: char) -> Self {
+        self.icon_glyph = glyph;
+        self
+    }
+
+    /// The flags of the [kitty keyboard protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/)
+    /// pushed while the TUI is shown. Terminals without the protocol ignore them,
+    /// so the keybinder should still work with the legacy encoding.
+    ///
+    /// With `DISAMBIGUATE_ESCAPE_CODES`, keys like Tab and Ctrl-I, Enter and Ctrl-M or
+    /// Enter and Shift-Enter are reported as different [`KeyEvent`]s.
+    /// Note that `REPORT_EVENT_TYPES` has no effect because only key presses are handled.
+    ///
+    /// The default value is `Some(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)`.
+    /// `None` disables the protocol.
+    pub fn keyboard_enhancement(mut self, flags: Option<KeyboardEnhancementFlags>) -> Self {
+        self.keyboard_enhancement = flags;
+        self
+    }
+
+    /// Style the input line, e.g. with [`PrefixHighlighter`].
+    /// The default value is no highlighter.
+    pub fn highlighter(mut self, highlighter: impl Highlighter + 'static) -> Self {
+        self.highlighter = Some(Arc::new(highlighter));
+        self
+    }
+
+    /// Show the modes as a tab bar, switched with [`Action::NextMode`] and [`Action::PrevMode`].
+    /// The TUI starts with the first mode.
+    /// The default value is no modes (and no tab bar).
+    pub fn modes(mut self, modes: Vec<TuiMode>) -> Self {
+        self.modes = modes;
+        self
+    }
+
+    /// How long a notification reported to [`Notifier`] is shown as a toast.
+    /// The default value is 5 seconds.
+    pub fn toast_duration(mut self, duration: Duration) -> Self {
+        self.toast_duration = duration;
+        self
+    }
+
+    /// The maximum number of rows an entry takes, including the line of [`TuiEntry::text`].
+    /// [`TuiEntry::lines`] beyond it are not shown. `1` shows only the text.
+    /// The default value is 2.
+    pub fn max_entry_height(mut self, height: usize) -> Self {
+        self.max_entry_height = height;
+        self
+    }
+
+    /// Record the keys, the queries and the merges of the batcher to `path` (overwritten on
+    /// each run). Replay it with [`record::replay`].
+    /// The default value is None (not recorded).
+    #[cfg(feature = "record")]
+    pub fn record(mut self, path: Option<PathBuf>) -> Self {
+        self.record = path;
+        self
+    }
+}
+
+/// Errors that can be distinguished from a cancel (`Ok(None)`) with `Report::downcast_ref`.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum TuiError {
+    #[error("No entry matched the initial input")]
+    NoMatch,
+    /// Returned on [`Action::Reload`].
+    ///
+    /// The batcher can't pull items from its sources again, so build the launcher (and its
+    /// sources) again and run it with a clone of the same [`Tui`]. The next run restores the
+    /// input, the mode and, where possible, the highlighted entry, without `select_one` and
+    /// `exit_zero`. Meanwhile the TUI stays on the screen showing that it's loading.
+    ///
+    /// ```no_run
+    /// # use ltrait::{Launcher, color_eyre::Result};
+    /// # use ltrait_ui_tui::{Tui, TuiConfig, TuiEntry, TuiError, Viewport, sample_keyconfig};
+    /// # async fn f() -> Result<()> {
+    /// let tui = Tui::new(TuiConfig::new(Viewport::Fullscreen, false, '>', ' ', sample_keyconfig));
+    ///
+    /// loop {
+    ///     let launcher = Launcher::default()
+    ///         // .add_source(..)
+    ///         .set_ui(tui.clone(), |c: &String| TuiEntry {
+    ///             text: (c.clone(), Default::default()),
+    ///             ..Default::default()
+    ///         });
+    ///
+    ///     match launcher.run().await {
+    ///         Err(e) if e.downcast_ref() == Some(&TuiError::Reload) => continue,
+    ///         result => break result,
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[error("Reload was requested")]
+    Reload,
 }
 
 type StyledText = (String, Style);
 
+/// A mode of the TUI (like rofi's modi), shown as a tab.
+///
+/// The batcher is shared by all modes, so a mode is the `prefix` prepended to the input before
+/// it's passed to the batcher. Route the components of each mode with the prefix, e.g. with
+/// `ltrait_extra::router::PrefixRouter`.
+/// The user never sees the prefix, and the input is kept when switching modes.
+#[derive(Debug, Clone)]
+pub struct TuiMode {
+    pub name: String,
+    pub prefix: String,
+}
+
+impl TuiMode {
+    pub fn new(name: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            prefix: prefix.into(),
+        }
+    }
+}
+
+/// An action listed in the action menu of an entry (opened with [`Action::ActionMenu`]).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryAction {
+    /// Returned from [`Tui::run_with_action`] (and [`ChosenAction`]) when chosen
+    pub id: String,
+    pub label: String,
+}
+
+impl EntryAction {
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+        }
+    }
+}
+
+/// The action menu, pinned to the entry it was opened for.
+struct Menu {
+    /// The id of the entry in the buffer
+    id: usize,
+    actions: Vec<EntryAction>,
+    /// The index of the highlighted action (0 at the top)
+    i: usize,
+}
+
 /// `<SelectingStatus> <icon> <title> <sub_string>`
 /// SelectingStatus in above is a char
+///
+/// Build it with `..Default::default()` so that new fields don't break the code.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "record", derive(serde::Serialize, serde::Deserialize))]
 pub struct TuiEntry {
     pub text: StyledText,
+    /// The path to the icon (e.g. `ltrait_source_desktop::DesktopEntry::icon`).
+    /// Ignored unless [`TuiConfig::icons`] is set.
+    pub icon: Option<PathBuf>,
+    /// The actions shown in the action menu. If empty, the menu can't be opened.
+    pub actions: Vec<EntryAction>,
+    /// Lines shown below `text` (e.g. a description), up to [`TuiConfig::max_entry_height`]
+    pub lines: Vec<StyledText>,
 }
 
 // なんのArc, Mutex, RwLockを使うか検討する必要がある。renderの中で使えないと意味ないし
@@ -168,16 +669,35 @@ where
     has_more: bool,
     tx: Option<mpsc::Sender<Event>>,
     selected: bool,
+    // Noneならjump modeではない。Someの中身は今まで入力されたlabel
+    jump: Option<String>,
+    // 最後にrenderした時に表示されていたbufferのindexの範囲
+    list_range: RwLock<Range<usize>>,
+    // Glyphで表示する時はNone
+    icons: Option<IconRenderer>,
+    // renderで記録してdraw_iconsで描画する (x, y, path)
+    icon_placements: RwLock<Vec<(u16, u16, PathBuf)>>,
+    // config.modesのindex
+    mode_i: usize,
+    // Someならaction menuを開いている
+    menu: Option<Menu>,
+    chosen_action: Option<String>,
+    notifier: Notifier,
+    error_log: bool,
+    reload: bool,
+    // reload前にhighlightしていたentryのtext。見つかるか全部sourceし終わるまで探す
+    restore_highlight: Option<String>,
+    #[cfg(feature = "record")]
+    recorder: Option<record::Recorder>,
 }
 
 impl<F> App<F>
 where
     F: Fn(&KeyEvent) -> Action + Clone,
 {
-    fn new(config: TuiConfig<F>) -> Self {
+    fn new(config: TuiConfig<F>, notifier: Notifier) -> Self {
         Self {
             has_more: true,
-            config,
             exit: false,
             selecting_i: 0,
             input: Input::default(),
@@ -185,6 +705,24 @@ where
             tx: None,
             cursor_pos: None.into(),
             selected: false,
+            jump: None,
+            list_range: RwLock::new(0..0),
+            icons: config
+                .icons
+                .map(IconProtocol::resolve)
+                .filter(|protocol| *protocol != IconProtocol::Glyph)
+                .map(IconRenderer::new),
+            icon_placements: RwLock::new(vec![]),
+            mode_i: 0,
+            menu: None,
+            chosen_action: None,
+            notifier,
+            error_log: false,
+            reload: false,
+            restore_highlight: None,
+            #[cfg(feature = "record")]
+            recorder: None,
+            config,
         }
     }
 }
@@ -203,6 +741,27 @@ pub enum Action {
     Up,
     Down,
     Input,
+    /// Overlay a label on each visible entry. Typing a label highlights (or selects) the entry.
+    Jump,
+    /// Switch to the next [`TuiMode`], keeping the input
+    NextMode,
+    /// Switch to the previous [`TuiMode`], keeping the input
+    PrevMode,
+    /// Open (or close) the menu of [`TuiEntry::actions`] of the highlighted entry
+    ActionMenu,
+    /// Open (or close) the log of everything reported to [`Notifier`]
+    ErrorLog,
+    /// Close the TUI to run the sources again. See [`TuiError::Reload`]
+    Reload,
+}
+
+/// Aborts the task when dropped.
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
 }
 
 impl Event {
@@ -226,16 +785,20 @@ impl<F> App<F>
 where
     F: Fn(&KeyEvent) -> Action + Clone,
 {
-    async fn run<Cusion: Send, B: Backend>(
+    async fn run<Cusion: Send, B: Backend + Write>(
         &mut self,
         terminal: &mut Terminal<B>,
         batcher: &mut Batcher<Cusion, TuiEntry>,
-    ) -> Result<Option<usize>> {
+    ) -> Result<Option<(usize, Option<String>)>> {
         let (tx, mut rx) = mpsc::channel(100);
 
-        tokio::spawn(Event::terminal_event_listener(tx.clone()));
+        // reloadで次のrunが始まったときにkeyを奪い合わないように、終わったら止める
+        let _listener = AbortOnDrop(tokio::spawn(Event::terminal_event_listener(tx.clone())));
         self.tx = Some(tx.clone());
 
+        // 先にbufferが埋まっている(prefetchした)場合もあるので最初に一回描画させる
+        let _ = tx.send(Event::Refresh).await;
+
         while !self.exit {
             let prepare = async {
                 if self.has_more {
@@ -248,6 +811,16 @@ where
                 }
             };
 
+            let toast_expiry = {
+                let expiry = self.notifier.next_expiry(self.config.toast_duration);
+                async move {
+                    match expiry {
+                        Some(expiry) => tokio::time::sleep_until(expiry.into()).await,
+                        None => std::future::pending().await,
+                    }
+                }
+            };
+
             select! {
                 // TODO: 毎回futureを生成し直していると
                 // dropした場合にバグるかも。あと必ず、rx.recvが早い場合何も表示されなくなっちゃうかも
@@ -259,6 +832,12 @@ where
                         let _ = tx.send(Event::Refresh).await;
 
                     self.has_more = has_more?;
+
+                    #[cfg(feature = "record")]
+                    self.record_merge();
+
+                    self.restore_highlight();
+                    self.pin_menu();
                     info!("Merged");
                 }
                 event_like = rx.recv().fuse() => {
@@ -267,24 +846,39 @@ where
 
                     match event_like {
                         Some(event) => {
-                            self.handle_events(event, batcher)
+                            if let Err(e) = self.handle_events(event, batcher)
                                 .await
-                                .wrap_err("handle events failed")?;
+                                .wrap_err("handle events failed") {
+                                    self.notifier.error(format!("{e:#}"));
+                            }
 
                             terminal.draw(|frame| self.draw(frame))?;
+                            self.draw_icons(terminal)?;
                         }
                     _ => bail!("the communication channel for event was unexpectedly closed.")
                     }
                 }
+                // toastを表示したり消したりするために描画し直す
+                _ = self.notifier.notified().fuse() => {
+                    let _ = tx.send(Event::Refresh).await;
+                }
+                _ = toast_expiry.fuse() => {
+                    let _ = tx.send(Event::Refresh).await;
+                }
             }
         }
 
-        Ok(if self.selected {
-            let mut pos = Position(self.buffer.len() - 1 - self.selecting_i);
-            Some(self.buffer.next(&mut pos).unwrap().1)
+        Ok(self.result())
+    }
+
+    /// The id of the selected entry and the chosen action, after the loop exited.
+    fn result(&mut self) -> Option<(usize, Option<String>)> {
+        if self.selected {
+            let chosen_action = self.chosen_action.take();
+            self.highlighted().map(|(_, id)| (*id, chosen_action))
         } else {
             None
-        })
+        }
     }
 
     fn draw(&self, frame: &mut Frame) {
@@ -294,6 +888,27 @@ where
         ))
     }
 
+    /// Draw the images recorded in `render`, which ratatui can't draw by itself.
+    fn draw_icons<B: Backend + Write>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let Some(icons) = self.icons.as_mut() else {
+            return Ok(());
+        };
+
+        let placements = std::mem::take(&mut *self.icon_placements.write().unwrap());
+        let backend = terminal.backend_mut();
+
+        if icons.draw(backend, &placements)? {
+            // 読み込めなかった画像をglyphで描画し直す
+            let _ = self.tx.as_ref().unwrap().try_send(Event::Refresh);
+        }
+
+        if let Some((x, y)) = *self.cursor_pos.read().unwrap() {
+            execute!(backend, MoveTo(x, y))?;
+        }
+
+        Ok(())
+    }
+
     async fn handle_events<Cusion: Send>(
         &mut self,
         event: Event,
@@ -304,14 +919,20 @@ where
             // crossterm also emits key release and repeat events on Windows.
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 info!("Handling KeyInput");
+
+                #[cfg(feature = "record")]
+                self.record(|_| record::RecordEvent::Key { key: key_event });
+
                 self.handle_key_event(key_event).await?
             }
             Event::Input => {
                 info!("Handling Input");
-                batcher.input(&mut self.buffer, self.input.value());
-                // 一回一番上に戻す
-                self.selecting_i = 0;
-                self.has_more = true;
+                let query = self.query();
+                batcher.input(&mut self.buffer, &query);
+                self.input_changed();
+
+                #[cfg(feature = "record")]
+                self.record(|_| record::RecordEvent::Query { query });
             }
             _ => {}
         };
@@ -319,6 +940,25 @@ where
     }
 
     async fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        if self.jump.is_some() {
+            self.handle_jump_key(key_event);
+            return Ok(());
+        }
+
+        if self.menu.is_some() {
+            self.handle_menu_key(key_event);
+            return Ok(());
+        }
+
+        if self.error_log {
+            if let Action::ExitWithoutSelect | Action::ErrorLog =
+                (self.config.keybinder)(&key_event)
+            {
+                self.error_log = false;
+            }
+            return Ok(());
+        }
+
         match (self.config.keybinder)(&key_event) {
             Action::Select => {
                 self.selected = true;
@@ -331,33 +971,283 @@ where
             Action::Down => {
                 self.selecting_i = self.selecting_i.saturating_sub(1);
             }
+            Action::Jump => {
+                if !self.buffer.is_empty() {
+                    self.jump = Some(String::new());
+                }
+            }
+            Action::ErrorLog => self.error_log = true,
+            Action::Reload => {
+                self.reload = true;
+                self.exit();
+            }
+            Action::ActionMenu => self.open_menu(),
+            Action::NextMode if !self.config.modes.is_empty() => self.switch_mode(true).await?,
+            Action::PrevMode if !self.config.modes.is_empty() => self.switch_mode(false).await?,
             _ => {
                 if !(self.input.cursor() == 0
                     && (key_event.code == KeyCode::Backspace || key_event.code == KeyCode::Left)
                     || self.input.cursor() == self.input.value().len()
                         && (key_event.code == KeyCode::Delete || key_event.code == KeyCode::Right))
                 {
+                    let before = self.input.value().to_owned();
                     self.input
                         .handle_event(&crossterm::event::Event::Key(key_event))
                         .ok_or_eyre("Failed to handle input")?;
 
-                    self.tx
-                        .as_mut()
-                        .unwrap()
-                        .send(Event::Input)
-                        .await
-                        .wrap_err("Failed to send Refresh")?;
+                    // カーソル移動だけならfilterし直さない(DedupFilterなどはinputごとに状態を持つので)
+                    if self.input.value() != before {
+                        self.tx
+                            .as_mut()
+                            .unwrap()
+                            .send(Event::Input)
+                            .await
+                            .wrap_err("Failed to send Refresh")?;
+                    }
                 }
             }
         }
         Ok(())
     }
 
+    fn open_menu(&mut self) {
+        if let Some((entry, id)) = self
+            .highlighted()
+            .filter(|(entry, _)| !entry.actions.is_empty())
+        {
+            self.menu = Some(Menu {
+                id: *id,
+                actions: entry.actions.clone(),
+                i: 0,
+            });
+        }
+    }
+
+    /// Keep the entry of the menu highlighted after a merge, or close the menu if the entry is
+    /// gone.
+    fn pin_menu(&mut self) {
+        let Some(menu) = &self.menu else {
+            return;
+        };
+
+        let mut pos = Position::default();
+        while let Some((_, id)) = self.buffer.next(&mut pos) {
+            if *id == menu.id {
+                self.selecting_i = self.buffer.len() - pos.0;
+                return;
+            }
+        }
+
+        self.menu = None;
+    }
+
+    fn handle_menu_key(&mut self, key_event: KeyEvent) {
+        let Some(menu) = self.menu.as_mut() else {
+            return;
+        };
+
+        match (self.config.keybinder)(&key_event) {
+            Action::Select => {
+                self.chosen_action = Some(menu.actions[menu.i].id.clone());
+                self.selected = true;
+                self.exit();
+            }
+            Action::ExitWithoutSelect | Action::ActionMenu => self.menu = None,
+            // menuは上から並ぶのでentryのリストとは逆
+            Action::Up => menu.i = menu.i.saturating_sub(1),
+            Action::Down => menu.i = (menu.i + 1).min(menu.actions.len() - 1),
+            _ => {}
+        }
+    }
+
+    fn restore_highlight(&mut self) {
+        let Some(text) = &self.restore_highlight else {
+            return;
+        };
+
+        let mut pos = Position::default();
+        while let Some((entry, _)) = self.buffer.next(&mut pos) {
+            if entry.text.0 == *text {
+                self.selecting_i = self.buffer.len() - pos.0;
+                self.restore_highlight = None;
+                return;
+            }
+        }
+
+        if !self.has_more {
+            self.restore_highlight = None;
+        }
+    }
+
+    /// The number of rows `entry` takes in the list.
+    fn entry_height(&self, entry: &TuiEntry) -> usize {
+        (1 + entry.lines.len()).min(self.config.max_entry_height.max(1))
+    }
+
+    /// The range of the buffer's indices shown in a list of `visible_height` rows.
+    ///
+    /// The highlighted entry is always shown, leaving `margin_below` rows below it if possible.
+    fn visible_range(&self, visible_height: usize) -> Range<usize> {
+        let items_count = self.buffer.len();
+        let Some(selected) = items_count.checked_sub(1 + self.selecting_i) else {
+            return 0..0;
+        };
+        let height = |i: usize| {
+            self.buffer
+                .next(&mut Position(i))
+                .map(|(entry, _)| self.entry_height(entry))
+                .unwrap_or_default()
+        };
+
+        // 選択されたアイテムが常に表示されるようにスクロール位置を計算
+        let margin_below = 2;
+
+        let mut start = selected;
+        let mut rows = height(selected);
+        while start > 0 && rows + height(start - 1) <= visible_height.saturating_sub(margin_below) {
+            start -= 1;
+            rows += height(start);
+        }
+
+        let mut end = selected + 1;
+        while end < items_count && rows + height(end) <= visible_height {
+            rows += height(end);
+            end += 1;
+        }
+
+        start..end
+    }
+
+    /// The highlighted entry.
+    fn highlighted(&self) -> Option<&(TuiEntry, usize)> {
+        let i = self.buffer.len().checked_sub(1 + self.selecting_i)?;
+        self.buffer.next(&mut Position(i))
+    }
+
+    fn handle_jump_key(&mut self, key_event: KeyEvent) {
+        let (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) =
+            (key_event.code, key_event.modifiers)
+        else {
+            // label以外のkeyが押されたらjump modeをやめる
+            self.jump = None;
+            return;
+        };
+
+        let typed = self.jump.get_or_insert_default();
+        typed.push(c);
+        let typed = typed.clone();
+
+        let targets = self.jump_targets();
+
+        if let Some((_, selecting_i)) = targets.iter().find(|(label, _)| *label == typed) {
+            self.selecting_i = *selecting_i;
+            self.jump = None;
+
+            if self.config.jump_select {
+                self.selected = true;
+                self.exit();
+            }
+        } else if !targets.iter().any(|(label, _)| label.starts_with(&typed)) {
+            self.jump = None;
+        }
+    }
+
+    /// `(label, selecting_i)` of the entries shown at the last render.
+    /// The entry nearest to the input gets the first label.
+    fn jump_targets(&self) -> Vec<(String, usize)> {
+        let range = self.list_range.read().unwrap().clone();
+        let items_count = self.buffer.len();
+
+        jump::labels(&self.config.jump_labels, range.len())
+            .into_iter()
+            .zip(range.rev())
+            .filter(|(_, i)| *i < items_count)
+            .map(|(label, i)| (label, items_count - 1 - i))
+            .collect()
+    }
+
+    async fn switch_mode(&mut self, next: bool) -> Result<()> {
+        let len = self.config.modes.len();
+        self.mode_i = if next {
+            (self.mode_i + 1) % len
+        } else {
+            (self.mode_i + len - 1) % len
+        };
+
+        self.tx
+            .as_mut()
+            .unwrap()
+            .send(Event::Input)
+            .await
+            .wrap_err("Failed to send Input")?;
+
+        Ok(())
+    }
+
+    /// The input and the name of the current mode, for [`LastQuery`].
+    fn last_query(&self) -> InputAndMode {
+        (
+            self.input.value().into(),
+            self.config
+                .modes
+                .get(self.mode_i)
+                .map(|mode| mode.name.clone()),
+        )
+    }
+
+    /// The input given to the batcher, with the prefix of the current mode.
+    fn query(&self) -> String {
+        match self.config.modes.get(self.mode_i) {
+            Some(mode) => format!("{}{}", mode.prefix, self.input.value()),
+            None => self.input.value().into(),
+        }
+    }
+
+    /// Reset the state that depends on the previous input. The buffer is reset by the batcher.
+    fn input_changed(&mut self) {
+        // 一回一番上に戻す
+        self.selecting_i = 0;
+        self.has_more = true;
+        self.restore_highlight = None;
+    }
+
     fn exit(&mut self) {
         self.exit = true;
     }
 }
 
+#[cfg(feature = "record")]
+impl<F> App<F>
+where
+    F: Fn(&KeyEvent) -> Action + Clone,
+{
+    /// Write a record if recording. A failure is reported once and stops the recording.
+    fn record(&mut self, event: impl FnOnce(&Self) -> record::RecordEvent) {
+        let Some(event) = self.recorder.is_some().then(|| event(self)) else {
+            return;
+        };
+
+        if let Err(e) = self.recorder.as_mut().unwrap().write(event) {
+            self.notifier
+                .error(format!("{:#}", e.wrap_err("failed to record the session")));
+            self.recorder = None;
+        }
+    }
+
+    /// Record the entries inserted by the last merge.
+    fn record_merge(&mut self) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+
+        let inserted = recorder.inserted(&self.buffer);
+        self.record(|app| record::RecordEvent::Merge {
+            inserted,
+            has_more: app.has_more,
+        });
+    }
+}
+
 pub fn sample_keyconfig(key: &KeyEvent) -> Action {
     match (key.code, key.modifiers) {
         (KeyCode::Enter, _) => Action::Select,
@@ -366,31 +1256,210 @@ pub fn sample_keyconfig(key: &KeyEvent) -> Action {
         | (KeyCode::Esc, _) => Action::ExitWithoutSelect,
         (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::CONTROL) => Action::Up,
         (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::CONTROL) => Action::Down,
+        (KeyCode::Char('s'), KeyModifiers::CONTROL) => Action::Jump,
+        (KeyCode::Char('o'), KeyModifiers::CONTROL) => Action::ActionMenu,
+        (KeyCode::Char('l'), KeyModifiers::CONTROL) => Action::ErrorLog,
+        (KeyCode::Char('r'), KeyModifiers::CONTROL) => Action::Reload,
+        (KeyCode::Tab, _) => Action::NextMode,
+        (KeyCode::BackTab, _) => Action::PrevMode,
         _ => Action::Input,
     }
 }
 
+impl<F> App<F>
+where
+    F: Fn(&KeyEvent) -> Action + Clone,
+{
+    /// Render the action menu as a popup at the bottom right of `area`.
+    fn render_menu(
+        &self,
+        actions: &[EntryAction],
+        menu_i: usize,
+        area: ratatui::prelude::Rect,
+        buffer: &mut ratatui::prelude::Buffer,
+    ) {
+        let width = actions
+            .iter()
+            .map(|action| action.label.chars().count() as u16 + 4)
+            .max()
+            .unwrap_or_default()
+            .max(16)
+            .min(area.width);
+        let height = (actions.len() as u16 + 2).min(area.height);
+
+        let popup = ratatui::prelude::Rect {
+            x: area.x + area.width - width,
+            y: area.y + area.height - height,
+            width,
+            height,
+        };
+
+        let items: Vec<_> = actions
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                let selecting_status = if i == menu_i {
+                    self.config.selecting
+                } else {
+                    self.config.no_selecting
+                };
+                ratatui::widgets::ListItem::new(format!("{selecting_status} {}", action.label))
+            })
+            .collect();
+
+        // 選択しているactionが見えるようにスクロール
+        let visible = height.saturating_sub(2) as usize;
+        let items: Vec<_> = items
+            .into_iter()
+            .skip((menu_i + 1).saturating_sub(visible))
+            .collect();
+
+        Clear.render(popup, buffer);
+        List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Actions"))
+            .render(popup, buffer);
+    }
+}
+
+impl<F> App<F>
+where
+    F: Fn(&KeyEvent) -> Action + Clone,
+{
+    fn notification_style(level: Level) -> Style {
+        use ratatui::style::Stylize as _;
+
+        match level {
+            Level::Warn => Style::new().yellow(),
+            Level::Error => Style::new().red(),
+        }
+    }
+
+    /// Render the active notifications at the top right of `area`, the newest at the bottom.
+    fn render_toasts(&self, area: ratatui::prelude::Rect, buffer: &mut ratatui::prelude::Buffer) {
+        let toasts = self.notifier.active(self.config.toast_duration);
+        // 多すぎるとエントリーが見えなくなるので最新の3件だけ
+        let toasts = &toasts[toasts.len().saturating_sub(3)..];
+
+        for (i, toast) in toasts.iter().enumerate() {
+            let width = (toast.message.chars().count() as u16 + 2).min(area.width);
+            let y = area.y + i as u16;
+
+            if y >= area.y + area.height {
+                break;
+            }
+
+            let toast_area = ratatui::prelude::Rect {
+                x: area.x + area.width - width,
+                y,
+                width,
+                height: 1,
+            };
+
+            Clear.render(toast_area, buffer);
+            Paragraph::new(format!(" {} ", toast.message))
+                .style(Self::notification_style(toast.level).add_modifier(Modifier::REVERSED))
+                .render(toast_area, buffer);
+        }
+    }
+
+    /// Render every notification in place of the entries, the newest at the bottom.
+    fn render_error_log(
+        &self,
+        area: ratatui::prelude::Rect,
+        buffer: &mut ratatui::prelude::Buffer,
+    ) {
+        let log = self.notifier.log();
+        let visible = area.height.saturating_sub(2) as usize;
+
+        let items: Vec<_> = log[log.len().saturating_sub(visible)..]
+            .iter()
+            .map(|n| {
+                let level = match n.level {
+                    Level::Warn => "WARN ",
+                    Level::Error => "ERROR",
+                };
+
+                ratatui::widgets::ListItem::new(Line::from(vec![
+                    Span::styled(level, Self::notification_style(n.level)),
+                    Span::raw(format!(
+                        " {:>4}s ago  {}",
+                        n.time.elapsed().as_secs(),
+                        n.message
+                    )),
+                ]))
+            })
+            .collect();
+
+        Clear.render(area, buffer);
+        List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Error log"))
+            .render(area, buffer);
+    }
+}
+
 impl<F> Widget for &App<F>
 where
     F: Fn(&KeyEvent) -> Action + Clone,
 {
     fn render(self, area: ratatui::prelude::Rect, buffer: &mut ratatui::prelude::Buffer) {
+        let tabs_height = if self.config.modes.is_empty() { 0 } else { 1 };
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(0), Constraint::Length(2)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(tabs_height),
+                    Constraint::Min(0),
+                    Constraint::Length(2),
+                ]
+                .as_ref(),
+            )
             .split(area);
 
+        // modeのタブ
+        if !self.config.modes.is_empty() {
+            Tabs::new(self.config.modes.iter().map(|mode| mode.name.as_str()))
+                .select(self.mode_i)
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+                .render(chunks[0], buffer);
+        }
+
         // エントリーの部分
         if !self.buffer.is_empty() {
-            let list_area = chunks[0];
+            let list_area = chunks[1];
 
             let items_count = self.buffer.len();
-            let mut items = Vec::with_capacity(items_count);
+            let reversed_selecting_index = items_count - 1 - self.selecting_i;
+
+            let Range {
+                start: start_index,
+                end: end_index,
+            } = self.visible_range(list_area.height as usize);
+
+            *self.list_range.write().unwrap() = start_index..end_index;
+
+            let labels: Option<std::collections::HashMap<_, _>> = self.jump.as_ref().map(|typed| {
+                self.jump_targets()
+                    .into_iter()
+                    .filter(|(label, _)| label.starts_with(typed.as_str()))
+                    .map(|(label, selecting_i)| (items_count - 1 - selecting_i, label))
+                    .collect()
+            });
+            let label_style = Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD);
 
-            let mut pos = Position::default();
+            let mut icon_placements = self.icon_placements.write().unwrap();
+            icon_placements.clear();
 
-            while let Some((entry, _)) = self.buffer.next(&mut pos) {
-                let is_selected = pos.0 - 1 == items_count - self.selecting_i - 1;
+            let mut items = Vec::with_capacity(end_index - start_index);
+            // 今のentryが始まる行(list_areaの上からの)
+            let mut row = 0;
+
+            let mut pos = Position(start_index);
+
+            while pos.0 < end_index
+                && let Some((entry, _)) = self.buffer.next(&mut pos)
+            {
+                let is_selected = pos.0 - 1 == reversed_selecting_index;
 
                 let selecting_status = if is_selected {
                     self.config.selecting
@@ -398,45 +1467,100 @@ where
                     self.config.no_selecting
                 };
 
-                let entry_text = format!("{} {}", selecting_status, entry.text.0);
+                let status = match labels.as_ref() {
+                    Some(labels) => match labels.get(&(pos.0 - 1)) {
+                        Some(label) => Span::styled(format!("{label:<2}"), label_style),
+                        None => Span::raw("  "),
+                    },
+                    None => Span::raw(format!("{selecting_status} ")),
+                };
                 let style = entry.text.1;
 
-                // リストアイテムを追加
-                items.push(ratatui::widgets::ListItem::new(entry_text).style(style));
-            }
+                let mut spans = vec![status];
 
-            let visible_height = list_area.height as usize;
-            let reversed_selecting_index = items_count - 1 - self.selecting_i;
+                if self.config.icons.is_some() {
+                    let blank = " ".repeat(ICON_WIDTH as usize + 1);
+
+                    spans.push(match (&entry.icon, &self.icons) {
+                        (Some(path), Some(icons)) if !icons.is_failed(path) => {
+                            icon_placements.push((
+                                list_area.x + 2,
+                                list_area.y + row as u16,
+                                path.clone(),
+                            ));
+                            Span::raw(blank)
+                        }
+                        (Some(_), _) => Span::raw(format!(
+                            "{:<width$}",
+                            self.config.icon_glyph,
+                            width = ICON_WIDTH as usize + 1
+                        )),
+                        (None, _) => Span::raw(blank),
+                    });
+                }
 
-            // 選択されたアイテムが常に表示されるようにスクロール位置を計算
-            let margin_below = 2;
-            let scroll_offset =
-                reversed_selecting_index.saturating_sub(visible_height - margin_below - 1);
+                spans.push(Span::raw(entry.text.0.as_str()));
 
-            let start_index = scroll_offset;
-            let end_index = (scroll_offset + visible_height).min(items_count);
+                // 2行目以降はstatusとiconの分だけ下げる
+                let indent = if self.config.icons.is_some() {
+                    2 + ICON_WIDTH as usize + 1
+                } else {
+                    2
+                };
+                let height = self.entry_height(entry);
 
-            let items: Vec<_> = items
-                .into_iter()
-                .skip(start_index)
-                .take(end_index - start_index)
-                .collect();
+                let lines: Vec<_> = std::iter::once(Line::from(spans))
+                    .chain(entry.lines.iter().take(height - 1).map(|(text, style)| {
+                        Line::from(vec![
+                            Span::raw(" ".repeat(indent)),
+                            Span::styled(text.as_str(), *style),
+                        ])
+                    }))
+                    .collect();
+
+                row += height;
+
+                // リストアイテムを追加
+                items.push(ratatui::widgets::ListItem::new(lines).style(style));
+            }
 
             List::new(items)
                 .block(Block::default())
                 .render(list_area, buffer);
+
+            if let Some(menu) = &self.menu {
+                self.render_menu(&menu.actions, menu.i, list_area, buffer);
+            }
         } else {
-            let list_area = chunks[0];
+            let list_area = chunks[1];
 
             Clear.render(list_area, buffer);
         }
+
+        if self.error_log {
+            self.render_error_log(chunks[1], buffer);
+        } else {
+            self.render_toasts(chunks[1], buffer);
+        }
         // テキスト入力部分
         {
-            let input_area = chunks[1];
-            let input_text = self.input.to_string();
+            let input_area = chunks[2];
+            let input_text = self.input.value();
+
+            let input_line = match &self.config.highlighter {
+                Some(highlighter) => {
+                    highlight::styled_line(input_text, highlighter.highlight(input_text))
+                }
+                None => Line::raw(input_text),
+            };
+
+            let mut block = Block::default().borders(Borders::TOP);
+            if self.has_more {
+                block = block.title(Line::from(" Loading… ").right_aligned());
+            }
 
-            Paragraph::new(input_text)
-                .block(Block::default().borders(Borders::TOP))
+            Paragraph::new(input_line)
+                .block(block)
                 .render(input_area, buffer);
 
             *self.cursor_pos.write().unwrap() = Some((
@@ -446,3 +1570,91 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(heights: &[usize]) -> App<fn(&KeyEvent) -> Action> {
+        let config = TuiConfig::new(
+            Viewport::Fullscreen,
+            false,
+            '>',
+            ' ',
+            sample_keyconfig as fn(&KeyEvent) -> Action,
+        )
+        .max_entry_height(3);
+        let mut app = App::new(config, Notifier::default());
+
+        for (i, height) in heights.iter().enumerate() {
+            app.buffer.push((
+                TuiEntry {
+                    text: (format!("{i}"), Style::default()),
+                    lines: vec![("description".into(), Style::default()); height - 1],
+                    ..Default::default()
+                },
+                i,
+            ));
+        }
+
+        app
+    }
+
+    #[test]
+    fn test_visible_range() {
+        let mut short = app(&[1, 1, 1, 1, 1, 1]);
+
+        // the last entry is highlighted first, with 2 rows left below it
+        assert_eq!(short.visible_range(4), 4..6);
+        short.selecting_i = 5;
+        assert_eq!(short.visible_range(4), 0..4);
+
+        // heights are clamped to max_entry_height (3)
+        let mut tall = app(&[2, 3, 1, 5, 2]);
+        assert_eq!(tall.visible_range(8), 2..5);
+        tall.selecting_i = 3;
+        assert_eq!(tall.visible_range(8), 0..3);
+
+        // the highlighted entry is shown even if it doesn't fit
+        assert_eq!(tall.visible_range(1), 1..2);
+        assert_eq!(tall.visible_range(0), 1..2);
+    }
+
+    #[test]
+    fn test_menu() {
+        let mut app = app(&[1, 1, 1]);
+        let entry = |i: usize, actions| {
+            let mut entry = app.buffer.next(&mut Position(i)).unwrap().clone();
+            entry.0.actions = actions;
+            entry
+        };
+        let actions = vec![
+            EntryAction::new("launch", "Launch"),
+            EntryAction::new("terminal", "Launch in terminal"),
+        ];
+
+        let (a, b, c) = (
+            entry(0, actions.clone()),
+            entry(1, actions),
+            entry(2, vec![]),
+        );
+        app.buffer = Buffer::from(vec![a.clone(), b.clone(), c.clone()]);
+        app.selecting_i = 1;
+        app.open_menu();
+        app.menu.as_mut().unwrap().i = 1;
+
+        // a merge moves the entry of the menu, which stays highlighted
+        app.buffer = Buffer::from(vec![b.clone(), a.clone(), c.clone()]);
+        app.pin_menu();
+        assert_eq!(app.highlighted().unwrap().1, 1);
+
+        app.handle_menu_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(app.result(), Some((1, Some("terminal".into()))));
+
+        // the menu is closed if the entry is gone
+        app.open_menu();
+        app.buffer = Buffer::from(vec![a, c]);
+        app.pin_menu();
+        assert!(app.menu.is_none());
+    }
+}
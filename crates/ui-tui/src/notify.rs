@@ -0,0 +1,97 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    pub time: Instant,
+}
+
+/// Reports non-fatal errors and warnings to the TUI, where they are shown as toasts and kept in
+/// the error log (opened with [`crate::Action::ErrorLog`]).
+///
+/// Get it with [`crate::Tui::notifier`] and pass it to components before the launcher runs, e.g.
+/// `ltrait_gen_calc::Calc::on_error`.
+///
+/// ```
+/// # use ltrait_ui_tui::{Tui, TuiConfig, Viewport, sample_keyconfig};
+/// let tui = Tui::new(TuiConfig::new(Viewport::Fullscreen, false, '>', ' ', sample_keyconfig));
+/// let notifier = tui.notifier();
+///
+/// notifier.warn("failed to read the history");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    log: Arc<Mutex<Vec<Notification>>>,
+    notify: Arc<Notify>,
+}
+
+impl Notifier {
+    pub fn warn(&self, message: impl Into<String>) {
+        self.push(Level::Warn, message.into());
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.push(Level::Error, message.into());
+    }
+
+    fn push(&self, level: Level, message: String) {
+        {
+            let mut log = self.log.lock().unwrap();
+
+            // 入力するたびに同じエラーが出る場合があるので連続したものはまとめる
+            match log.last_mut() {
+                Some(last) if last.level == level && last.message == message => {
+                    last.time = Instant::now();
+                }
+                _ => log.push(Notification {
+                    level,
+                    message,
+                    time: Instant::now(),
+                }),
+            }
+        }
+
+        self.notify.notify_one();
+    }
+
+    /// All notifications reported so far, oldest first.
+    pub fn log(&self) -> Vec<Notification> {
+        self.log.lock().unwrap().clone()
+    }
+
+    /// The notifications younger than `duration`, oldest first.
+    pub(crate) fn active(&self, duration: Duration) -> Vec<Notification> {
+        self.log
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| n.time.elapsed() < duration)
+            .cloned()
+            .collect()
+    }
+
+    /// When the next active toast should disappear.
+    pub(crate) fn next_expiry(&self, duration: Duration) -> Option<Instant> {
+        self.active(duration)
+            .iter()
+            .map(|n| n.time + duration)
+            .min()
+    }
+
+    /// Wait until something is reported.
+    pub(crate) async fn notified(&self) {
+        self.notify.notified().await
+    }
+}
@@ -0,0 +1,328 @@
+//! Record sessions and replay them without a terminal, e.g. to attach a reproduction of a
+//! ranking or rendering bug to a bug report.
+//!
+//! A recording is a JSON Lines file written by [`crate::TuiConfig::record`]. Each line is a
+//! [`Record`].
+//!
+//! ```no_run
+//! # use ltrait::color_eyre::Result;
+//! # use ltrait_ui_tui::{TuiConfig, Viewport, record, sample_keyconfig};
+//! # async fn f() -> Result<()> {
+//! let config = TuiConfig::new(Viewport::Fullscreen, false, '>', ' ', sample_keyconfig);
+//! let recording = std::io::BufReader::new(std::fs::File::open("session.jsonl")?);
+//!
+//! let replay = record::replay(config, recording, 80, 24).await?;
+//! println!("{:?}", replay.frames.last());
+//! # Ok(())
+//! # }
+//! ```
+
+use ltrait::{
+    color_eyre::eyre::{Result, WrapErr, bail},
+    ui::{Buffer, Position},
+};
+
+use crossterm::event::KeyEvent;
+use ratatui::{Terminal, backend::TestBackend};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tui_input::Input;
+
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufRead, BufWriter, Write},
+    path::Path,
+    time::Instant,
+};
+
+use crate::{Action, App, Notifier, TuiConfig, TuiEntry};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    /// Milliseconds since the TUI started. Replays ignore it
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: RecordEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordEvent {
+    /// The input and the index of the mode the TUI started with (restored by a reload)
+    Start {
+        input: String,
+        mode_i: usize,
+    },
+    Key {
+        key: KeyEvent,
+    },
+    /// The input given to the batcher changed
+    Query {
+        query: String,
+    },
+    /// The batcher merged items. `inserted` is the new entries and their ids, with their indices
+    /// in the buffer after the merge (in ascending order). The buffer is emptied by `Query`
+    Merge {
+        inserted: Vec<(usize, TuiEntry, usize)>,
+        has_more: bool,
+    },
+}
+
+/// Writes a [`Record`] per line. Every line is flushed so that a crash keeps the recording.
+pub(crate) struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+    // このqueryで記録したentryのid。mergeごとにbuffer全体を書き出さないように
+    recorded: HashSet<usize>,
+}
+
+impl Recorder {
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .wrap_err_with(|| format!("failed to create the recording {}", path.display()))?;
+
+        Ok(Self {
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+            recorded: HashSet::new(),
+        })
+    }
+
+    /// The entries of `buffer` not recorded yet since the last `Query`, with their indices.
+    pub(crate) fn inserted(
+        &mut self,
+        buffer: &Buffer<(TuiEntry, usize)>,
+    ) -> Vec<(usize, TuiEntry, usize)> {
+        let mut pos = Position::default();
+
+        std::iter::from_fn(|| buffer.next(&mut pos))
+            .enumerate()
+            .filter(|(_, (_, id))| self.recorded.insert(*id))
+            .map(|(i, (entry, id))| (i, entry.clone(), *id))
+            .collect()
+    }
+
+    pub(crate) fn write(&mut self, event: RecordEvent) -> Result<()> {
+        if let RecordEvent::Query { .. } = event {
+            self.recorded.clear();
+        }
+
+        let record = Record {
+            time: self.start.elapsed().as_millis() as u64,
+            event,
+        };
+
+        serde_json::to_writer(&mut self.writer, &record)?;
+        writeln!(self.writer)?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// The result of [`replay`].
+pub struct Replay {
+    /// The screen after each record
+    pub frames: Vec<ratatui::buffer::Buffer>,
+    /// The id and the chosen action of the selected entry, as returned by
+    /// [`crate::Tui::run_with_action`] (before `compute_cushion`)
+    pub selected: Option<(usize, Option<String>)>,
+}
+
+/// Feed the records of `recording` through the TUI drawn on a [`TestBackend`] of `width` x
+/// `height`.
+///
+/// `config` should be the one used while recording; the keys are bound again with its
+/// keybinder. Fails if the replay diverges from the recording (the query doesn't match).
+pub async fn replay<F>(
+    config: TuiConfig<F>,
+    recording: impl BufRead,
+    width: u16,
+    height: u16,
+) -> Result<Replay>
+where
+    F: Fn(&KeyEvent) -> Action + Clone,
+{
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut app = App::new(config, Notifier::default());
+    // recordingには画像を描画できないのでglyphにする
+    app.icons = None;
+
+    // handle_key_eventが送るEvent::Inputは捨てて、Queryのrecordが来たところで反映する
+    let (tx, mut rx) = mpsc::channel(100);
+    app.tx = Some(tx);
+
+    let mut frames = vec![];
+    // mergeのrecordには差分しかないので、ここでbufferを組み立てる
+    let mut entries: Vec<(TuiEntry, usize)> = vec![];
+
+    for (i, line) in recording.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: Record = serde_json::from_str(&line)
+            .wrap_err_with(|| format!("invalid record at line {}", i + 1))?;
+
+        match record.event {
+            RecordEvent::Start { input, mode_i } => {
+                app.input = Input::new(input);
+                app.mode_i = mode_i;
+            }
+            RecordEvent::Key { key } => {
+                if let Err(e) = app.handle_key_event(key).await {
+                    app.notifier.error(format!("{e:#}"));
+                }
+                while rx.try_recv().is_ok() {}
+            }
+            RecordEvent::Query { query } => {
+                if app.query() != query {
+                    bail!(
+                        "the replay diverged at line {}: the query is {:?} but {:?} was recorded",
+                        i + 1,
+                        app.query(),
+                        query
+                    );
+                }
+
+                entries.clear();
+                app.buffer = Buffer::default();
+                app.input_changed();
+            }
+            RecordEvent::Merge { inserted, has_more } => {
+                for (index, entry, id) in inserted {
+                    if index > entries.len() {
+                        bail!("invalid record at line {}: no index {index}", i + 1);
+                    }
+                    entries.insert(index, (entry, id));
+                }
+
+                app.buffer = Buffer::from(entries.clone());
+                app.has_more = has_more;
+                app.restore_highlight();
+                app.pin_menu();
+            }
+        }
+
+        terminal.draw(|frame| app.draw(frame))?;
+        app.icon_placements.write().unwrap().clear();
+        frames.push(terminal.backend().buffer().clone());
+
+        if app.exit {
+            break;
+        }
+    }
+
+    Ok(Replay {
+        selected: app.result(),
+        frames,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Viewport, sample_keyconfig};
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use futures::executor::block_on;
+
+    fn entry(text: &str) -> TuiEntry {
+        TuiEntry {
+            text: (text.into(), Default::default()),
+            ..Default::default()
+        }
+    }
+
+    fn line(event: RecordEvent) -> String {
+        serde_json::to_string(&Record { time: 0, event }).unwrap()
+    }
+
+    fn screen(buffer: &ratatui::buffer::Buffer) -> String {
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn test_replay() {
+        let key = |code| RecordEvent::Key {
+            key: KeyEvent::new(code, KeyModifiers::NONE),
+        };
+
+        let recording = [
+            RecordEvent::Start {
+                input: String::new(),
+                mode_i: 0,
+            },
+            RecordEvent::Merge {
+                inserted: vec![(0, entry("foot"), 1)],
+                has_more: true,
+            },
+            RecordEvent::Merge {
+                inserted: vec![(0, entry("firefox"), 0)],
+                has_more: false,
+            },
+            key(KeyCode::Char('f')),
+            RecordEvent::Query { query: "f".into() },
+            RecordEvent::Merge {
+                inserted: vec![(0, entry("firefox"), 0), (1, entry("foot"), 1)],
+                has_more: false,
+            },
+            key(KeyCode::Up),
+            key(KeyCode::Enter),
+        ]
+        .map(line)
+        .join("\n");
+
+        let config = TuiConfig::new(Viewport::Fullscreen, false, '>', ' ', sample_keyconfig);
+        let replay = block_on(replay(config, recording.as_bytes(), 30, 10)).unwrap();
+
+        assert_eq!(replay.frames.len(), 8);
+        assert!(screen(&replay.frames[1]).contains("foot"));
+        assert!(screen(&replay.frames[2]).contains("firefox"));
+        assert_eq!(replay.selected, Some((0, None)));
+    }
+
+    #[test]
+    fn test_replay_diverged() {
+        let recording = [
+            RecordEvent::Key {
+                key: KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+            },
+            RecordEvent::Query { query: "b".into() },
+        ]
+        .map(line)
+        .join("\n");
+
+        let config = TuiConfig::new(Viewport::Fullscreen, false, '>', ' ', sample_keyconfig);
+
+        assert!(block_on(replay(config, recording.as_bytes(), 30, 10)).is_err());
+    }
+
+    #[test]
+    fn test_inserted() {
+        let path = std::env::temp_dir().join(format!("ltrait-ui-tui-{}.jsonl", std::process::id()));
+        let mut recorder = Recorder::create(&path).unwrap();
+
+        let indices = |inserted: Vec<(usize, TuiEntry, usize)>| -> Vec<_> {
+            inserted.into_iter().map(|(i, _, id)| (i, id)).collect()
+        };
+
+        let buffer = Buffer::from(vec![(entry("a"), 0), (entry("b"), 1)]);
+        assert_eq!(indices(recorder.inserted(&buffer)), [(0, 0), (1, 1)]);
+
+        let buffer = Buffer::from(vec![(entry("a"), 0), (entry("c"), 2), (entry("b"), 1)]);
+        assert_eq!(indices(recorder.inserted(&buffer)), [(1, 2)]);
+
+        // a new query empties the buffer
+        recorder
+            .write(RecordEvent::Query { query: "x".into() })
+            .unwrap();
+        assert_eq!(
+            indices(recorder.inserted(&buffer)),
+            [(0, 0), (1, 2), (2, 1)]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}